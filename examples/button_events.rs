@@ -0,0 +1,19 @@
+extern crate ev3dev_lang_rust;
+
+use ev3dev_lang_rust::button_events::ButtonEventKind;
+use ev3dev_lang_rust::{Button, ButtonId, Ev3Result};
+
+fn main() -> Ev3Result<()> {
+    let button = Button::new()?;
+
+    for event in button.event_stream()? {
+        let event = event?;
+        match event.kind {
+            ButtonEventKind::Press | ButtonEventKind::Release => {}
+            ButtonEventKind::LongPress if event.button == ButtonId::Backspace => break,
+            _ => println!("{:?}", event),
+        }
+    }
+
+    Ok(())
+}
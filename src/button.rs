@@ -0,0 +1,575 @@
+//! Buttons read from the Linux input subsystem.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::rc::Rc;
+
+use paste::paste;
+
+use crate::button_events::ButtonEventStream;
+use crate::driver::DRIVER_PATH;
+use crate::utils::OrErr;
+use crate::{ButtonId, ButtonSet, Ev3Error, Ev3Result};
+
+const KEY_BUF_LEN: usize = 96;
+const EVIOCGKEY: u32 = 2_153_792_792;
+/// `EVIOCGABS(0)`, add the axis code to read the `input_absinfo` of an absolute axis.
+const EVIOCGABS: u32 = 2_149_074_240;
+
+const ABS_X: u32 = 0x00;
+const ABS_Y: u32 = 0x01;
+
+/// Input device of the brick buttons.
+#[cfg(feature = "ev3")]
+const EV3_BUTTON_DEVICE: &str = "/dev/input/by-path/platform-gpio_keys-event";
+
+/// Key codes of the brick buttons.
+#[cfg(feature = "ev3")]
+const EV3_KEY_CODES: [(ButtonId, u32); 6] = [
+    (ButtonId::Up, 103),
+    (ButtonId::Down, 108),
+    (ButtonId::Left, 105),
+    (ButtonId::Right, 106),
+    (ButtonId::Enter, 28),
+    (ButtonId::Backspace, 14),
+];
+
+/// Name of the PiStorms input device.
+#[cfg(any(feature = "brickpi", feature = "brickpi3"))]
+const PISTORMS_DEVICE_NAME: &str = "PiStorms";
+
+/// Key codes of the PiStorms: the GO button (`KEY_ENTER`) and the touchscreen (`BTN_TOUCH`).
+#[cfg(any(feature = "brickpi", feature = "brickpi3"))]
+const PISTORMS_KEY_CODES: [(ButtonId, u32); 2] = [(ButtonId::Enter, 28), (ButtonId::Touch, 330)];
+
+/// Input device of push buttons configured with the `gpio-keys` device tree overlay.
+#[cfg(any(feature = "brickpi", feature = "brickpi3"))]
+const GPIO_KEYS_DEVICE: &str = "/dev/input/by-path/platform-gpio_keys-event";
+
+/// Helper struct for ButtonFileHandler.
+struct FileMapEntry {
+    pub file: File,
+    pub buffer_cache: [u8; KEY_BUF_LEN],
+}
+// Manually implement Debug cause `buffer_cache` does not implement Debug.
+impl fmt::Debug for FileMapEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileMapEntry")
+            .field("file", &self.file)
+            .finish()
+    }
+}
+
+/// Helper struct for ButtonFileHandler.
+#[derive(Debug)]
+struct ButtonMapEntry {
+    pub file_name: String,
+    pub key_code: u32,
+}
+
+type ButtonChangeHandler = Box<dyn Fn(ButtonSet)>;
+type ButtonHandler = Box<dyn Fn(bool)>;
+
+/// Reads the current value of an absolute axis with the EVIOCGABS ioctl.
+fn read_abs_value(file: &File, axis: u32) -> Ev3Result<i32> {
+    let mut info = libc::input_absinfo {
+        value: 0,
+        minimum: 0,
+        maximum: 0,
+        fuzz: 0,
+        flat: 0,
+        resolution: 0,
+    };
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            ((EVIOCGABS + axis) as i32).try_into().unwrap(),
+            &mut info,
+        )
+    };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(info.value)
+}
+
+/// Returns the `/dev/input/event*` file of the input device with the given name.
+fn find_input_device(device_name: &str) -> Ev3Result<String> {
+    for entry in fs::read_dir(Path::new(DRIVER_PATH).join("input"))? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let event_name = file_name.to_str().or_err()?;
+        if !event_name.starts_with("event") {
+            continue;
+        }
+
+        if let Ok(name) = fs::read_to_string(entry.path().join("device").join("name")) {
+            if name.trim() == device_name {
+                return Ok(format!("/dev/input/{event_name}"));
+            }
+        }
+    }
+
+    Err(Ev3Error::NotConnected {
+        device: device_name.to_owned(),
+        port: None,
+    })
+}
+
+/// This implementation depends on the availability of the EVIOCGKEY ioctl
+/// to be able to read the button state buffer. See Linux kernel source
+/// in /include/uapi/linux/input.h for details.
+struct ButtonFileHandler {
+    file_map: HashMap<String, FileMapEntry>,
+    button_map: HashMap<ButtonId, ButtonMapEntry>,
+    button_change_handler: Option<ButtonChangeHandler>,
+    button_handlers: HashMap<ButtonId, ButtonHandler>,
+    pressed_buttons: ButtonSet,
+}
+
+impl std::fmt::Debug for ButtonFileHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ButtonFileHandler")
+            .field("file_map", &self.file_map)
+            .field("button_map", &self.button_map)
+            .field(
+                "button_change_handler",
+                &self.button_change_handler.is_some(),
+            )
+            .field("button_handlers", &self.button_map.keys())
+            .field("pressed_buttons", &self.pressed_buttons)
+            .finish()
+    }
+}
+
+impl ButtonFileHandler {
+    /// Create a new instance.
+    fn new() -> Self {
+        ButtonFileHandler {
+            file_map: HashMap::new(),
+            button_map: HashMap::new(),
+            button_change_handler: None,
+            button_handlers: HashMap::new(),
+            pressed_buttons: ButtonSet::new(),
+        }
+    }
+
+    /// Add a button to the file handler.
+    /// Fails if the key code does not fit into the key state buffer of `EVIOCGKEY`.
+    fn add_button(&mut self, button: ButtonId, file_name: &str, key_code: u32) -> Ev3Result<()> {
+        if key_code >= KEY_BUF_LEN as u32 * 8 {
+            return Err(Ev3Error::InternalError {
+                msg: format!(
+                    "Key code {key_code} of button {button:?} is out of range (0-{})",
+                    KEY_BUF_LEN * 8 - 1
+                ),
+            });
+        }
+        if !self.file_map.contains_key(file_name) {
+            let file = File::open(file_name)?;
+            let buffer_cache = [0u8; KEY_BUF_LEN];
+
+            self.file_map
+                .insert(file_name.to_owned(), FileMapEntry { file, buffer_cache });
+        }
+
+        self.button_map.insert(
+            button,
+            ButtonMapEntry {
+                file_name: file_name.to_owned(),
+                key_code,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Sets an event handler for the given button.
+    fn set_button_handler(&mut self, button: ButtonId, handler: Option<ButtonHandler>) {
+        if let Some(listener) = handler {
+            self.button_handlers.insert(button, listener);
+        } else {
+            self.button_handlers.remove(&button);
+        }
+    }
+
+    /// Sets an event handler for any button state change.
+    fn set_button_change_handler(&mut self, handler: Option<ButtonChangeHandler>) {
+        self.button_change_handler = handler;
+    }
+
+    /// Gets a copy of the currently pressed buttons.
+    fn get_pressed_buttons(&self) -> ButtonSet {
+        self.pressed_buttons
+    }
+
+    /// Check if a button is pressed.
+    fn get_button_state(&self, button: ButtonId) -> bool {
+        self.pressed_buttons.contains(button)
+    }
+
+    /// Create an event stream with the same buttons, that reads from separate file handles.
+    fn event_stream(&self) -> Ev3Result<ButtonEventStream> {
+        let mut stream = ButtonEventStream::new();
+        for (button, entry) in &self.button_map {
+            stream.add_button(*button, &entry.file_name, entry.key_code)?;
+        }
+        Ok(stream)
+    }
+
+    /// Returns the touch position, see `Button::get_touch_position()`.
+    fn get_touch_position(&self) -> Ev3Result<Option<(i32, i32)>> {
+        if !self.get_button_state(ButtonId::Touch) {
+            return Ok(None);
+        }
+
+        let file = &self.file_map[&self.button_map[&ButtonId::Touch].file_name].file;
+        Ok(Some((
+            read_abs_value(file, ABS_X)?,
+            read_abs_value(file, ABS_Y)?,
+        )))
+    }
+
+    /// Check for currently pressed buttons. If the new state differs from the
+    /// old state, call the appropriate button event handlers.
+    fn process(&mut self) {
+        for entry in self.file_map.values_mut() {
+            unsafe {
+                libc::ioctl(
+                    entry.file.as_raw_fd(),
+                    (EVIOCGKEY as i32).try_into().unwrap(),
+                    &mut entry.buffer_cache,
+                );
+            }
+        }
+
+        let old_pressed_buttons = self.pressed_buttons;
+        self.pressed_buttons.clear();
+
+        for (
+            button,
+            ButtonMapEntry {
+                file_name,
+                key_code,
+            },
+        ) in self.button_map.iter()
+        {
+            let buffer = &self.file_map[file_name].buffer_cache;
+
+            if (buffer[(key_code / 8) as usize] & 1 << (key_code % 8)) != 0 {
+                self.pressed_buttons.insert(*button);
+            }
+        }
+
+        let difference = old_pressed_buttons.symmetric_difference(&self.pressed_buttons);
+
+        for button in difference.iter() {
+            if let Some(handler) = self.button_handlers.get(&button) {
+                handler(self.get_button_state(button));
+            }
+        }
+
+        if !difference.is_empty() {
+            if let Some(ref handler) = self.button_change_handler {
+                handler(self.get_pressed_buttons());
+            }
+        }
+    }
+}
+
+/// Button handler. Opens the corresponding `/dev/input` file handlers.
+///
+/// With the `ev3` feature `Button::new()` opens the brick buttons.
+/// With the `brickpi` and `brickpi3` features `Button::pistorms()` opens the GO button and the touchscreen
+/// of a PiStorms and `Button::gpio_keys()` opens push buttons connected to GPIO pins.
+/// `Button::with_mapping()` works for any input device.
+///
+/// This implementation depends on the availability of the EVIOCGKEY ioctl
+/// to be able to read the button state buffer. See Linux kernel source
+/// in /include/uapi/linux/input.h for details.
+///
+#[cfg_attr(feature = "ev3", doc = "```no_run")]
+#[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+/// use ev3dev_lang_rust::Button;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut button = Button::new()?;
+///
+/// button.set_down_handler(|is_pressed| {
+///     println!("Is 'down' pressed: {is_pressed}");
+/// });
+///
+/// loop {
+///     button.process();
+///
+///     println!("Is 'up' pressed: {}", button.is_up());
+///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
+///
+///     thread::sleep(Duration::from_millis(100));
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Button {
+    button_handler: Rc<RefCell<ButtonFileHandler>>,
+}
+
+impl Button {
+    /// Ev3 brick button handler. Opens the corresponding `/dev/input` file handlers.
+    #[cfg(feature = "ev3")]
+    pub fn new() -> Ev3Result<Self> {
+        Self::with_mapping(EV3_BUTTON_DEVICE, &EV3_KEY_CODES)
+    }
+
+    /// PiStorms button handler. The GO button is reported as `ButtonId::Enter`,
+    /// touching the screen as `ButtonId::Touch`.
+    ///
+    /// ```no_run
+    /// use ev3dev_lang_rust::Button;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let button = Button::pistorms()?;
+    ///
+    /// loop {
+    ///     button.process();
+    ///     if button.is_enter() {
+    ///         println!("GO pressed");
+    ///     }
+    ///     if let Some((x, y)) = button.get_touch_position()? {
+    ///         println!("Touched at {x}, {y}");
+    ///     }
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    /// # }
+    /// ```
+    #[cfg(any(feature = "brickpi", feature = "brickpi3"))]
+    pub fn pistorms() -> Ev3Result<Self> {
+        Self::with_device_name(PISTORMS_DEVICE_NAME, &PISTORMS_KEY_CODES)
+    }
+
+    /// Handler for push buttons connected to GPIO pins with the `gpio-keys` device tree overlay,
+    /// e.g. `dtoverlay=gpio-key,gpio=17,keycode=28`. `mapping` assigns the configured key codes to buttons.
+    ///
+    /// ```no_run
+    /// use ev3dev_lang_rust::{Button, ButtonId};
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let button = Button::gpio_keys(&[(ButtonId::Enter, 28), (ButtonId::Backspace, 14)])?;
+    ///
+    /// button.process();
+    /// println!("Pressed buttons: {:?}", button.get_pressed_buttons());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(any(feature = "brickpi", feature = "brickpi3"))]
+    pub fn gpio_keys(mapping: &[(ButtonId, u32)]) -> Ev3Result<Self> {
+        Self::with_mapping(GPIO_KEYS_DEVICE, mapping)
+    }
+
+    /// Button handler for the input device with the given name, as reported by `/sys/class/input/event*/device/name`.
+    pub fn with_device_name(device_name: &str, mapping: &[(ButtonId, u32)]) -> Ev3Result<Self> {
+        Self::with_mapping(&find_input_device(device_name)?, mapping)
+    }
+
+    /// Button handler for a custom input device, e.g. a keypad or a USB keyboard.
+    ///
+    /// `mapping` assigns a linux key code (see `/include/uapi/linux/input-event-codes.h`) to each button.
+    /// The `is_*` and `set_*_handler` functions only work for the named buttons that are part of the mapping.
+    /// Fails for key codes above `KEY_MAX` (767).
+    ///
+    /// ```no_run
+    /// use ev3dev_lang_rust::{Button, ButtonId};
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// // Arrow keys, return and escape of a USB keyboard, plus the space bar.
+    /// let button = Button::with_mapping(
+    ///     "/dev/input/by-id/usb-keyboard-event-kbd",
+    ///     &[
+    ///         (ButtonId::Up, 103),
+    ///         (ButtonId::Down, 108),
+    ///         (ButtonId::Left, 105),
+    ///         (ButtonId::Right, 106),
+    ///         (ButtonId::Enter, 28),
+    ///         (ButtonId::Backspace, 1),
+    ///         (ButtonId::Custom(0), 57),
+    ///     ],
+    /// )?;
+    ///
+    /// button.process();
+    /// println!("Space pressed: {}", button.is_pressed(ButtonId::Custom(0)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ```
+    /// use ev3dev_lang_rust::{Button, ButtonId};
+    ///
+    /// // Any readable file is accepted as device, but the key code is out of range.
+    /// assert!(Button::with_mapping("/dev/null", &[(ButtonId::Enter, 768)]).is_err());
+    /// assert!(Button::with_mapping("/dev/null", &[(ButtonId::Enter, 767)]).is_ok());
+    /// ```
+    pub fn with_mapping(file_name: &str, mapping: &[(ButtonId, u32)]) -> Ev3Result<Self> {
+        let mut handler = ButtonFileHandler::new();
+
+        for (button, key_code) in mapping {
+            handler.add_button(*button, file_name, *key_code)?;
+        }
+
+        Ok(Self {
+            button_handler: Rc::new(RefCell::new(handler)),
+        })
+    }
+
+    /// Check for currently pressed buttons. If the new state differs from the
+    /// old state, call the appropriate button event handlers.
+    #[cfg_attr(feature = "ev3", doc = "```no_run")]
+    #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+    /// use ev3dev_lang_rust::Button;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut button = Button::new()?;
+    ///
+    /// button.set_down_handler(|is_pressed| {
+    ///     println!("Is 'down' pressed: {is_pressed}");
+    /// });
+    ///
+    /// loop {
+    ///     button.process();
+    ///
+    ///     println!("Is 'up' pressed: {}", button.is_up());
+    ///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
+    ///
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    /// # }
+    /// ```
+    pub fn process(&self) {
+        self.button_handler.borrow_mut().process()
+    }
+
+    /// Get all pressed buttons by name.
+    ///
+    #[cfg_attr(feature = "ev3", doc = "```no_run")]
+    #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+    /// use ev3dev_lang_rust::Button;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let button = Button::new()?;
+    ///
+    /// loop {
+    ///     button.process();
+    ///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    /// # }
+    /// ```
+    pub fn get_pressed_buttons(&self) -> ButtonSet {
+        self.button_handler.borrow().get_pressed_buttons()
+    }
+
+    /// Check if the given button is pressed.
+    pub fn is_pressed(&self, button: ButtonId) -> bool {
+        self.button_handler.borrow().get_button_state(button)
+    }
+
+    /// Returns the raw `(x, y)` position of the touchscreen if `ButtonId::Touch` is pressed,
+    /// or `None` if the screen is not touched or no touchscreen is mapped.
+    pub fn get_touch_position(&self) -> Ev3Result<Option<(i32, i32)>> {
+        self.button_handler.borrow().get_touch_position()
+    }
+
+    /// Set an event handler, that is called by `process()` if the pressed state of the given button changes.
+    pub fn set_handler(&mut self, button: ButtonId, handler: impl Fn(bool) + 'static) {
+        self.button_handler
+            .borrow_mut()
+            .set_button_handler(button, Some(Box::new(handler)));
+    }
+
+    /// Removes the event handler of the given button.
+    pub fn remove_handler(&mut self, button: ButtonId) {
+        self.button_handler
+            .borrow_mut()
+            .set_button_handler(button, None);
+    }
+
+    /// Set an event handler, that is called by `process()` if any button state changes.
+    /// Has a set of all pressed buttons as parameter.
+    ///
+    #[cfg_attr(feature = "ev3", doc = "```no_run")]
+    #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+    /// use ev3dev_lang_rust::Button;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut button = Button::new()?;
+    ///
+    /// button.set_change_handler(|pressed_buttons| {
+    ///     println!("pressed buttons: {:?}", pressed_buttons);
+    /// });
+    ///
+    /// loop {
+    ///     button.process();
+    ///     thread::sleep(Duration::from_millis(100));
+    /// }
+    /// # }
+    /// ```
+    pub fn set_change_handler(&mut self, handler: impl Fn(ButtonSet) + 'static) {
+        self.button_handler
+            .borrow_mut()
+            .set_button_change_handler(Some(Box::new(handler)))
+    }
+
+    /// Removes the change event handler.
+    pub fn remove_change_handler(&mut self) {
+        self.button_handler
+            .borrow_mut()
+            .set_button_change_handler(None)
+    }
+
+    /// Create a blocking stream of button events with gesture recognition.
+    ///
+    /// The stream reads the `EV_KEY` events of the input devices instead of polling the button state,
+    /// so `process()` is not required. It uses its own file handles and can be moved to another thread.
+    ///
+    #[cfg_attr(feature = "ev3", doc = "```no_run")]
+    #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+    /// use ev3dev_lang_rust::button_events::ButtonEventKind;
+    /// use ev3dev_lang_rust::{Button, ButtonId};
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let button = Button::new()?;
+    ///
+    /// for event in button.event_stream()? {
+    ///     let event = event?;
+    ///     if event.kind == ButtonEventKind::LongPress && event.button == ButtonId::Backspace {
+    ///         break;
+    ///     }
+    ///     println!("{:?}", event);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn event_stream(&self) -> Ev3Result<ButtonEventStream> {
+        self.button_handler.borrow().event_stream()
+    }
+
+    ev3_button_functions!(up, Up);
+    ev3_button_functions!(down, Down);
+    ev3_button_functions!(left, Left);
+    ev3_button_functions!(right, Right);
+    ev3_button_functions!(enter, Enter);
+    ev3_button_functions!(backspace, Backspace);
+}
@@ -0,0 +1,535 @@
+//! Event driven button input with gesture recognition.
+//!
+//! The `ButtonEventStream` reads `EV_KEY` events from `/dev/input` devices and blocks until
+//! the next event is available, so buttons no longer have to be polled with `Button::process()`.
+//! Raw press and release events are combined to higher level gestures by the `GestureRecognizer`.
+//!
+//! # Example
+#![cfg_attr(feature = "ev3", doc = "```no_run")]
+#![cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+//! use ev3dev_lang_rust::button_events::ButtonEventKind;
+//! use ev3dev_lang_rust::Button;
+//!
+//! # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+//! let button = Button::new()?;
+//!
+//! for event in button.event_stream()? {
+//!     let event = event?;
+//!     match event.kind {
+//!         ButtonEventKind::Click => println!("Clicked {}", event.button),
+//!         ButtonEventKind::DoubleClick => println!("Double clicked {}", event.button),
+//!         ButtonEventKind::LongPress => println!("Long pressed {}", event.button),
+//!         ButtonEventKind::Chord(ref buttons) => println!("Chord {:?}", buttons),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Read;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{ButtonId, ButtonSet, Ev3Error, Ev3Result};
+
+/// Input event type of key presses, see `/include/uapi/linux/input-event-codes.h`.
+const EV_KEY: u16 = 0x01;
+
+/// Kind of a button event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ButtonEventKind {
+    /// The button was pressed down.
+    Press,
+    /// The button was released.
+    Release,
+    /// The button was pressed and released without a long press, repeat or chord.
+    Click,
+    /// The button was clicked a second time within the double click time.
+    /// The second click is reported as `Click` as well.
+    DoubleClick,
+    /// The button is held down for the long press time. Sent once per press.
+    LongPress,
+    /// The button is still held down, sent in the auto-repeat interval.
+    Repeat,
+    /// The button was pressed while other buttons are held down.
+    /// Contains all held buttons.
+    /// Buttons that are part of a chord do not produce clicks, long presses or repeats.
+    Chord(ButtonSet),
+}
+
+/// A button event with the button and the time it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ButtonEvent {
+    /// The button that caused the event.
+    pub button: ButtonId,
+    /// Kind of the event.
+    pub kind: ButtonEventKind,
+    /// Time of the event since the unix epoch, based on the kernel timestamps of the input events.
+    pub timestamp: Duration,
+}
+
+/// Timing of the synthesized gestures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GestureConfig {
+    /// Time a button must be held down until a `LongPress` is reported.
+    pub long_press: Duration,
+    /// Maximal time between the release of the first and the press of the second click of a `DoubleClick`.
+    pub double_click: Duration,
+    /// Time a button must be held down until the first `Repeat` is reported, `None` disables auto-repeat.
+    pub repeat_delay: Option<Duration>,
+    /// Interval of the following `Repeat` events.
+    pub repeat_interval: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            long_press: Duration::from_millis(1000),
+            double_click: Duration::from_millis(300),
+            repeat_delay: Some(Duration::from_millis(500)),
+            repeat_interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// State of a button that is currently held down.
+#[derive(Debug, Clone)]
+struct HeldButton {
+    button: ButtonId,
+    pressed_at: Duration,
+    long_press_sent: bool,
+    next_repeat: Option<Duration>,
+    repeated: bool,
+    in_chord: bool,
+}
+
+impl HeldButton {
+    /// Returns the time of the next timer event of this button.
+    fn next_deadline(&self, config: &GestureConfig) -> Option<Duration> {
+        if self.in_chord {
+            return None;
+        }
+        let long_press = if self.long_press_sent {
+            None
+        } else {
+            Some(self.pressed_at + config.long_press)
+        };
+        match (long_press, self.next_repeat) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Synthesizes gestures from raw press and release events.
+///
+/// The recognizer does not read any files, so it can also be fed with events from other sources.
+/// Timer based gestures (`LongPress`, `Repeat`) are reported by `handle_time()`.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::button_events::{ButtonEventKind, GestureConfig, GestureRecognizer};
+/// use ev3dev_lang_rust::ButtonId;
+/// use std::time::Duration;
+///
+/// let mut recognizer = GestureRecognizer::new(GestureConfig::default());
+/// let ms = Duration::from_millis;
+///
+/// recognizer.handle_key(ButtonId::Enter, true, ms(0));
+/// let events = recognizer.handle_key(ButtonId::Enter, false, ms(100));
+/// assert_eq!(events[1].kind, ButtonEventKind::Click);
+///
+/// recognizer.handle_key(ButtonId::Enter, true, ms(200));
+/// let events = recognizer.handle_key(ButtonId::Enter, false, ms(300));
+/// assert_eq!(events[2].kind, ButtonEventKind::DoubleClick);
+///
+/// recognizer.handle_key(ButtonId::Up, true, ms(1000));
+/// assert_eq!(recognizer.next_deadline(), Some(ms(1500)));
+/// let events = recognizer.handle_time(ms(2000));
+/// let kinds: Vec<_> = events.into_iter().map(|event| event.kind).collect();
+/// assert_eq!(kinds.iter().filter(|kind| **kind == ButtonEventKind::Repeat).count(), 6);
+/// assert!(kinds.contains(&ButtonEventKind::LongPress));
+/// ```
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    held: Vec<HeldButton>,
+    last_click: Option<(ButtonId, Duration)>,
+}
+
+impl GestureRecognizer {
+    /// Create a new recognizer with the given timing.
+    pub fn new(config: GestureConfig) -> Self {
+        GestureRecognizer {
+            config,
+            held: Vec::new(),
+            last_click: None,
+        }
+    }
+
+    /// Returns the gesture timing.
+    pub fn get_config(&self) -> GestureConfig {
+        self.config
+    }
+
+    /// Sets the gesture timing. Already held buttons keep their repeat schedule.
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.config = config;
+    }
+
+    /// Returns all currently held buttons.
+    pub fn get_held_buttons(&self) -> ButtonSet {
+        self.held.iter().map(|held| held.button).collect()
+    }
+
+    /// Returns the time of the next timer based event, if any button is held down.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        self.held
+            .iter()
+            .filter_map(|held| held.next_deadline(&self.config))
+            .min()
+    }
+
+    /// Process a press (`pressed = true`) or release of the `button` at `timestamp`.
+    ///
+    /// Timer events up to `timestamp` are reported first.
+    /// Presses of already held buttons and releases of unknown buttons are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::button_events::{ButtonEvent, ButtonEventKind::*, GestureRecognizer};
+    /// use ev3dev_lang_rust::{ButtonId, ButtonSet};
+    /// use std::time::Duration;
+    ///
+    /// let mut recognizer = GestureRecognizer::default();
+    /// let ms = Duration::from_millis;
+    /// let kinds = |events: Vec<ButtonEvent>| events.into_iter().map(|event| event.kind).collect::<Vec<_>>();
+    ///
+    /// // Pressing Right while Left is held down is a chord of both buttons.
+    /// recognizer.handle_key(ButtonId::Left, true, ms(0));
+    /// let chord: ButtonSet = [ButtonId::Left, ButtonId::Right].into_iter().collect();
+    /// assert_eq!(kinds(recognizer.handle_key(ButtonId::Right, true, ms(50))), [Press, Chord(chord)]);
+    ///
+    /// // Buttons of a chord do not long press, repeat or click.
+    /// assert!(recognizer.handle_time(ms(2000)).is_empty());
+    /// assert_eq!(kinds(recognizer.handle_key(ButtonId::Left, false, ms(2100))), [Release]);
+    /// assert_eq!(kinds(recognizer.handle_key(ButtonId::Right, false, ms(2150))), [Release]);
+    ///
+    /// // The second press comes 400 ms after the first click, too late for a double click.
+    /// recognizer.handle_key(ButtonId::Enter, true, ms(3000));
+    /// assert_eq!(kinds(recognizer.handle_key(ButtonId::Enter, false, ms(3100))), [Release, Click]);
+    /// recognizer.handle_key(ButtonId::Enter, true, ms(3500));
+    /// assert_eq!(kinds(recognizer.handle_key(ButtonId::Enter, false, ms(3600))), [Release, Click]);
+    ///
+    /// // The third press follows within 300 ms.
+    /// recognizer.handle_key(ButtonId::Enter, true, ms(3800));
+    /// assert_eq!(
+    ///     kinds(recognizer.handle_key(ButtonId::Enter, false, ms(3900))),
+    ///     [Release, Click, DoubleClick]
+    /// );
+    /// ```
+    pub fn handle_key(
+        &mut self,
+        button: ButtonId,
+        pressed: bool,
+        timestamp: Duration,
+    ) -> Vec<ButtonEvent> {
+        let mut events = self.handle_time(timestamp);
+        let event = |kind| ButtonEvent {
+            button,
+            kind,
+            timestamp,
+        };
+
+        if pressed {
+            if self.held.iter().any(|held| held.button == button) {
+                return events;
+            }
+
+            events.push(event(ButtonEventKind::Press));
+
+            let in_chord = !self.held.is_empty();
+            self.held.push(HeldButton {
+                button,
+                pressed_at: timestamp,
+                long_press_sent: false,
+                next_repeat: self.config.repeat_delay.map(|delay| timestamp + delay),
+                repeated: false,
+                in_chord,
+            });
+
+            if in_chord {
+                for held in &mut self.held {
+                    held.in_chord = true;
+                }
+                events.push(event(ButtonEventKind::Chord(self.get_held_buttons())));
+            }
+        } else {
+            let index = match self.held.iter().position(|held| held.button == button) {
+                Some(index) => index,
+                None => return events,
+            };
+            let held = self.held.remove(index);
+
+            events.push(event(ButtonEventKind::Release));
+
+            if !held.in_chord && !held.long_press_sent && !held.repeated {
+                events.push(event(ButtonEventKind::Click));
+
+                match self.last_click.take() {
+                    Some((last_button, released_at))
+                        if last_button == button
+                            && held.pressed_at.saturating_sub(released_at)
+                                <= self.config.double_click =>
+                    {
+                        events.push(event(ButtonEventKind::DoubleClick));
+                    }
+                    _ => self.last_click = Some((button, timestamp)),
+                }
+            } else {
+                self.last_click = None;
+            }
+        }
+
+        events
+    }
+
+    /// Report all `LongPress` and `Repeat` events that are due at `now`, ordered by their timestamp.
+    pub fn handle_time(&mut self, now: Duration) -> Vec<ButtonEvent> {
+        let mut events = Vec::new();
+
+        for held in self.held.iter_mut().filter(|held| !held.in_chord) {
+            let long_press_at = held.pressed_at + self.config.long_press;
+            if !held.long_press_sent && long_press_at <= now {
+                held.long_press_sent = true;
+                events.push(ButtonEvent {
+                    button: held.button,
+                    kind: ButtonEventKind::LongPress,
+                    timestamp: long_press_at,
+                });
+            }
+
+            while let Some(repeat_at) = held.next_repeat.filter(|repeat_at| *repeat_at <= now) {
+                held.repeated = true;
+                held.next_repeat =
+                    Some(repeat_at + self.config.repeat_interval.max(Duration::from_millis(1)));
+                events.push(ButtonEvent {
+                    button: held.button,
+                    kind: ButtonEventKind::Repeat,
+                    timestamp: repeat_at,
+                });
+            }
+        }
+
+        events.sort_by_key(|event| event.timestamp);
+        events
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        GestureRecognizer::new(GestureConfig::default())
+    }
+}
+
+/// Helper struct for ButtonEventStream.
+#[derive(Debug)]
+struct EventSource {
+    file: File,
+    key_map: HashMap<u32, ButtonId>,
+}
+
+/// Blocking stream of button events read from `/dev/input` devices.
+///
+/// Iterating the stream blocks until the next event is available.
+/// Use `next_event()` to wait with a timeout.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::button_events::ButtonEventStream;
+/// use ev3dev_lang_rust::ButtonId;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut stream = ButtonEventStream::new();
+/// stream.add_button(ButtonId::Enter, "/dev/input/by-path/platform-gpio_keys-event", 28)?;
+///
+/// while let Some(event) = stream.next_event(Some(Duration::from_secs(10)))? {
+///     println!("{:?}", event);
+/// }
+/// println!("No button event within 10 seconds");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ButtonEventStream {
+    sources: HashMap<String, EventSource>,
+    recognizer: GestureRecognizer,
+    pending: VecDeque<ButtonEvent>,
+}
+
+impl ButtonEventStream {
+    /// Create a new stream without any buttons.
+    pub fn new() -> Self {
+        ButtonEventStream {
+            sources: HashMap::new(),
+            recognizer: GestureRecognizer::default(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Add a button with the given key code of the input device `file_name`.
+    pub fn add_button(
+        &mut self,
+        button: ButtonId,
+        file_name: &str,
+        key_code: u32,
+    ) -> Ev3Result<()> {
+        if !self.sources.contains_key(file_name) {
+            let file = File::open(file_name)?;
+            self.sources.insert(
+                file_name.to_owned(),
+                EventSource {
+                    file,
+                    key_map: HashMap::new(),
+                },
+            );
+        }
+
+        if let Some(source) = self.sources.get_mut(file_name) {
+            source.key_map.insert(key_code, button);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the gesture timing.
+    pub fn get_config(&self) -> GestureConfig {
+        self.recognizer.get_config()
+    }
+
+    /// Sets the gesture timing.
+    pub fn set_config(&mut self, config: GestureConfig) {
+        self.recognizer.set_config(config);
+    }
+
+    /// Wait for the next button event.
+    ///
+    /// Returns `None` if no event happened within the `timeout`.
+    /// If the `timeout` is `None` it will wait an infinite time.
+    pub fn next_event(&mut self, timeout: Option<Duration>) -> Ev3Result<Option<ButtonEvent>> {
+        if self.sources.is_empty() {
+            return Err(Ev3Error::InternalError {
+                msg: "Button event stream has no buttons".to_owned(),
+            });
+        }
+
+        let end = timeout.map(|timeout| now() + timeout);
+
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+
+            let current = now();
+            if let Some(end) = end {
+                if current >= end {
+                    return Ok(None);
+                }
+            }
+
+            let deadline = match (self.recognizer.next_deadline(), end) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            let poll_timeout = match deadline {
+                // Round up, so the deadline is due after the poll returns.
+                Some(deadline) => deadline
+                    .saturating_sub(current)
+                    .as_micros()
+                    .div_ceil(1000)
+                    .min(i32::MAX as u128) as i32,
+                None => -1,
+            };
+
+            self.poll(poll_timeout)?;
+
+            let events = self.recognizer.handle_time(now());
+            self.pending.extend(events);
+        }
+    }
+
+    /// Wait up to `timeout` milliseconds for input events and feed them to the recognizer.
+    fn poll(&mut self, timeout: i32) -> Ev3Result<()> {
+        let sources: Vec<&mut EventSource> = self.sources.values_mut().collect();
+        let mut fds: Vec<libc::pollfd> = sources
+            .iter()
+            .map(|source| libc::pollfd {
+                fd: source.file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+
+        let result = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+        if result < 0 {
+            let error = std::io::Error::last_os_error();
+            if error.kind() == std::io::ErrorKind::Interrupted {
+                return Ok(());
+            }
+            return Err(error.into());
+        }
+
+        for (source, fd) in sources.into_iter().zip(fds) {
+            if fd.revents & libc::POLLIN == 0 {
+                continue;
+            }
+
+            let mut buffer = [0u8; mem::size_of::<libc::input_event>()];
+            source.file.read_exact(&mut buffer)?;
+            let event: libc::input_event =
+                unsafe { std::ptr::read_unaligned(buffer.as_ptr() as *const libc::input_event) };
+
+            // Value 2 is the kernel auto-repeat, repeats are synthesized by the recognizer instead.
+            if event.type_ != EV_KEY || event.value > 1 {
+                continue;
+            }
+
+            if let Some(button) = source.key_map.get(&(event.code as u32)) {
+                let timestamp =
+                    Duration::new(event.time.tv_sec as u64, event.time.tv_usec as u32 * 1000);
+                let events = self
+                    .recognizer
+                    .handle_key(*button, event.value == 1, timestamp);
+                self.pending.extend(events);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ButtonEventStream {
+    fn default() -> Self {
+        ButtonEventStream::new()
+    }
+}
+
+impl Iterator for ButtonEventStream {
+    type Item = Ev3Result<ButtonEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event(None).transpose()
+    }
+}
+
+/// Returns the current time since the unix epoch, the clock of the input event timestamps.
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
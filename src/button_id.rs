@@ -0,0 +1,207 @@
+//! Typed button identifiers and a compact set of buttons.
+
+use std::fmt;
+use std::iter::FromIterator;
+
+/// Number of bit indices reserved for the named buttons.
+const NAMED_BUTTONS: usize = 32;
+/// Number of 64 bit words to store all named and custom buttons.
+const WORDS: usize = (NAMED_BUTTONS + 256).div_ceil(64);
+
+/// Identifies a button of the brick or of a custom input device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ButtonId {
+    /// The up button.
+    Up,
+    /// The down button.
+    Down,
+    /// The left button.
+    Left,
+    /// The right button.
+    Right,
+    /// The center button.
+    Enter,
+    /// The back button.
+    Backspace,
+    /// A touchscreen, pressed while it is touched.
+    Touch,
+    /// Any other button, e.g. a key of a USB keyboard, identified by a user defined number.
+    Custom(u8),
+}
+
+impl ButtonId {
+    /// All named buttons, without `Custom`.
+    pub const NAMED: [ButtonId; 7] = [
+        ButtonId::Up,
+        ButtonId::Down,
+        ButtonId::Left,
+        ButtonId::Right,
+        ButtonId::Enter,
+        ButtonId::Backspace,
+        ButtonId::Touch,
+    ];
+
+    /// Bit index of the button in a `ButtonSet`.
+    fn index(self) -> usize {
+        match self {
+            ButtonId::Up => 0,
+            ButtonId::Down => 1,
+            ButtonId::Left => 2,
+            ButtonId::Right => 3,
+            ButtonId::Enter => 4,
+            ButtonId::Backspace => 5,
+            ButtonId::Touch => 6,
+            ButtonId::Custom(id) => NAMED_BUTTONS + id as usize,
+        }
+    }
+
+    /// Inverse of `index()`.
+    fn from_index(index: usize) -> Option<ButtonId> {
+        if index >= NAMED_BUTTONS {
+            u8::try_from(index - NAMED_BUTTONS)
+                .ok()
+                .map(ButtonId::Custom)
+        } else {
+            ButtonId::NAMED
+                .iter()
+                .copied()
+                .find(|id| id.index() == index)
+        }
+    }
+}
+
+impl fmt::Display for ButtonId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ButtonId::Up => write!(f, "up"),
+            ButtonId::Down => write!(f, "down"),
+            ButtonId::Left => write!(f, "left"),
+            ButtonId::Right => write!(f, "right"),
+            ButtonId::Enter => write!(f, "enter"),
+            ButtonId::Backspace => write!(f, "backspace"),
+            ButtonId::Touch => write!(f, "touch"),
+            ButtonId::Custom(id) => write!(f, "custom-{id}"),
+        }
+    }
+}
+
+/// A set of buttons stored as bitset.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::{ButtonId, ButtonSet};
+///
+/// let mut pressed = ButtonSet::new();
+/// pressed.insert(ButtonId::Enter);
+/// pressed.insert(ButtonId::Custom(42));
+///
+/// assert!(pressed.contains(ButtonId::Enter));
+/// assert!(!pressed.contains(ButtonId::Up));
+/// assert_eq!(pressed.len(), 2);
+///
+/// let chord: ButtonSet = [ButtonId::Enter, ButtonId::Custom(42)].into_iter().collect();
+/// assert_eq!(pressed, chord);
+/// assert_eq!(pressed.iter().collect::<Vec<_>>(), vec![ButtonId::Enter, ButtonId::Custom(42)]);
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ButtonSet {
+    bits: [u64; WORDS],
+}
+
+impl ButtonSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        ButtonSet::default()
+    }
+
+    /// Adds a button to the set. Returns `true` if it was not present before.
+    pub fn insert(&mut self, button: ButtonId) -> bool {
+        let (word, mask) = Self::position(button);
+        let inserted = self.bits[word] & mask == 0;
+        self.bits[word] |= mask;
+        inserted
+    }
+
+    /// Removes a button from the set. Returns `true` if it was present before.
+    pub fn remove(&mut self, button: ButtonId) -> bool {
+        let (word, mask) = Self::position(button);
+        let removed = self.bits[word] & mask != 0;
+        self.bits[word] &= !mask;
+        removed
+    }
+
+    /// Checks if the button is part of the set.
+    pub fn contains(&self, button: ButtonId) -> bool {
+        let (word, mask) = Self::position(button);
+        self.bits[word] & mask != 0
+    }
+
+    /// Checks if all buttons of `other` are part of this set.
+    pub fn is_superset(&self, other: &ButtonSet) -> bool {
+        self.bits
+            .iter()
+            .zip(other.bits.iter())
+            .all(|(a, b)| a & b == *b)
+    }
+
+    /// Returns the number of buttons in the set.
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Checks if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|word| *word == 0)
+    }
+
+    /// Removes all buttons.
+    pub fn clear(&mut self) {
+        self.bits = [0; WORDS];
+    }
+
+    /// Returns the buttons that are in exactly one of both sets.
+    pub fn symmetric_difference(&self, other: &ButtonSet) -> ButtonSet {
+        let mut result = *self;
+        for (a, b) in result.bits.iter_mut().zip(other.bits.iter()) {
+            *a ^= b;
+        }
+        result
+    }
+
+    /// Iterates over the buttons in the order of the `ButtonId` variants.
+    pub fn iter(&self) -> impl Iterator<Item = ButtonId> + '_ {
+        (0..WORDS * 64)
+            .filter(move |index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+            .filter_map(ButtonId::from_index)
+    }
+
+    fn position(button: ButtonId) -> (usize, u64) {
+        let index = button.index();
+        (index / 64, 1 << (index % 64))
+    }
+}
+
+impl fmt::Debug for ButtonSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<ButtonId> for ButtonSet {
+    fn from_iter<I: IntoIterator<Item = ButtonId>>(iter: I) -> Self {
+        let mut set = ButtonSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<ButtonId> for ButtonSet {
+    fn extend<I: IntoIterator<Item = ButtonId>>(&mut self, iter: I) {
+        for button in iter {
+            self.insert(button);
+        }
+    }
+}
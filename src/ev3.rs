@@ -1,17 +1,7 @@
 //! EV3 specific features
 
 use std::fs;
-
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::fmt;
-use std::fs::File;
-use std::os::unix::io::AsRawFd;
 use std::path::Path;
-use std::rc::Rc;
-
-use paste::paste;
 
 use crate::driver::DRIVER_PATH;
 use crate::utils::OrErr;
@@ -178,307 +168,3 @@ impl Led {
         self.set_right_color(color)
     }
 }
-
-const KEY_BUF_LEN: usize = 96;
-const EVIOCGKEY: u32 = 2_153_792_792;
-
-/// Helper struct for ButtonFileHandler.
-struct FileMapEntry {
-    pub file: File,
-    pub buffer_cache: [u8; KEY_BUF_LEN],
-}
-// Manually implement Debug cause `buffer_cache` does not implement Debug.
-impl fmt::Debug for FileMapEntry {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("FileMapEntry")
-            .field("file", &self.file)
-            .finish()
-    }
-}
-
-/// Helper struct for ButtonFileHandler.
-#[derive(Debug)]
-struct ButtonMapEntry {
-    pub file_name: String,
-    pub key_code: u32,
-}
-
-type ButtonChangeHandler = Box<dyn Fn(HashSet<String>)>;
-type ButtonHandler = Box<dyn Fn(bool)>;
-
-/// This implementation depends on the availability of the EVIOCGKEY ioctl
-/// to be able to read the button state buffer. See Linux kernel source
-/// in /include/uapi/linux/input.h for details.
-struct ButtonFileHandler {
-    file_map: HashMap<String, FileMapEntry>,
-    button_map: HashMap<String, ButtonMapEntry>,
-    button_change_handler: Option<ButtonChangeHandler>,
-    button_handlers: HashMap<String, ButtonHandler>,
-    pressed_buttons: HashSet<String>,
-}
-
-impl std::fmt::Debug for ButtonFileHandler {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ButtonFileHandler")
-            .field("file_map", &self.file_map)
-            .field("button_map", &self.button_map)
-            .field(
-                "button_change_handler",
-                &self.button_change_handler.is_some(),
-            )
-            .field("button_handlers", &self.button_map.keys())
-            .field("pressed_buttons", &self.pressed_buttons)
-            .finish()
-    }
-}
-
-impl ButtonFileHandler {
-    /// Create a new instance.
-    fn new() -> Self {
-        ButtonFileHandler {
-            file_map: HashMap::new(),
-            button_map: HashMap::new(),
-            button_change_handler: None,
-            button_handlers: HashMap::new(),
-            pressed_buttons: HashSet::new(),
-        }
-    }
-
-    /// Add a button to the file handler.
-    fn add_button(&mut self, name: &str, file_name: &str, key_code: u32) -> Ev3Result<()> {
-        if !self.file_map.contains_key(file_name) {
-            let file = File::open(file_name)?;
-            let buffer_cache = [0u8; KEY_BUF_LEN];
-
-            self.file_map
-                .insert(file_name.to_owned(), FileMapEntry { file, buffer_cache });
-        }
-
-        self.button_map.insert(
-            name.to_owned(),
-            ButtonMapEntry {
-                file_name: file_name.to_owned(),
-                key_code,
-            },
-        );
-
-        Ok(())
-    }
-
-    /// Sets an event handler for the given button.
-    fn set_button_handler(&mut self, name: &str, handler: Option<ButtonHandler>) {
-        if let Some(listener) = handler {
-            self.button_handlers.insert(name.to_owned(), listener);
-        } else {
-            self.button_handlers.remove(name);
-        }
-    }
-
-    /// Sets an event handler for any button state change.
-    fn set_button_change_handler(&mut self, handler: Option<ButtonChangeHandler>) {
-        self.button_change_handler = handler;
-    }
-
-    /// Gets a copy of the currently pressed buttons.
-    fn get_pressed_buttons(&self) -> HashSet<String> {
-        self.pressed_buttons.clone()
-    }
-
-    /// Check if a button is pressed.
-    fn get_button_state(&self, name: &str) -> bool {
-        self.pressed_buttons.contains(name)
-    }
-
-    /// Check for currently pressed buttons. If the new state differs from the
-    /// old state, call the appropriate button event handlers.
-    fn process(&mut self) {
-        for entry in self.file_map.values_mut() {
-            unsafe {
-                libc::ioctl(
-                    entry.file.as_raw_fd(),
-                    (EVIOCGKEY as i32).try_into().unwrap(),
-                    &mut entry.buffer_cache,
-                );
-            }
-        }
-
-        let old_pressed_buttons = self.pressed_buttons.clone();
-        self.pressed_buttons.clear();
-
-        for (
-            btn_name,
-            ButtonMapEntry {
-                file_name,
-                key_code,
-            },
-        ) in self.button_map.iter()
-        {
-            let buffer = &self.file_map[file_name].buffer_cache;
-
-            if (buffer[(key_code / 8) as usize] & 1 << (key_code % 8)) != 0 {
-                self.pressed_buttons.insert(btn_name.to_owned());
-            }
-        }
-
-        let difference = old_pressed_buttons.symmetric_difference(&self.pressed_buttons);
-
-        let mut difference_count = 0;
-        for button in difference {
-            difference_count += 1;
-            if self.button_handlers.contains_key(button) {
-                self.button_handlers[button](self.get_button_state(button));
-            }
-        }
-
-        if difference_count > 0 {
-            if let Some(ref handler) = self.button_change_handler {
-                handler(self.get_pressed_buttons());
-            }
-        }
-    }
-}
-
-/// Ev3 brick button handler. Opens the corresponding `/dev/input` file handlers.
-///
-/// This implementation depends on the availability of the EVIOCGKEY ioctl
-/// to be able to read the button state buffer. See Linux kernel source
-/// in /include/uapi/linux/input.h for details.
-///
-/// ```no_run
-/// use ev3dev_lang_rust::Button;
-/// use std::thread;
-/// use std::time::Duration;
-///
-/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
-/// let mut button = Button::new()?;
-///
-/// button.set_down_handler(|is_pressed| {
-///     println!("Is 'down' pressed: {is_pressed}");
-/// });
-///
-/// loop {
-///     button.process();
-///
-///     println!("Is 'up' pressed: {}", button.is_up());
-///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
-///
-///     thread::sleep(Duration::from_millis(100));
-/// }
-/// # }
-/// ```
-#[derive(Debug, Clone)]
-pub struct Button {
-    button_handler: Rc<RefCell<ButtonFileHandler>>,
-}
-
-impl Button {
-    /// Ev3 brick button handler. Opens the corresponding `/dev/input` file handlers.
-    pub fn new() -> Ev3Result<Self> {
-        let mut handler = ButtonFileHandler::new();
-
-        handler.add_button("up", "/dev/input/by-path/platform-gpio_keys-event", 103)?;
-        handler.add_button("down", "/dev/input/by-path/platform-gpio_keys-event", 108)?;
-        handler.add_button("left", "/dev/input/by-path/platform-gpio_keys-event", 105)?;
-        handler.add_button("right", "/dev/input/by-path/platform-gpio_keys-event", 106)?;
-        handler.add_button("enter", "/dev/input/by-path/platform-gpio_keys-event", 28)?;
-        handler.add_button(
-            "backspace",
-            "/dev/input/by-path/platform-gpio_keys-event",
-            14,
-        )?;
-
-        Ok(Self {
-            button_handler: Rc::new(RefCell::new(handler)),
-        })
-    }
-
-    /// Check for currently pressed buttons. If the new state differs from the
-    /// old state, call the appropriate button event handlers.
-    /// ```no_run
-    /// use ev3dev_lang_rust::Button;
-    /// use std::thread;
-    /// use std::time::Duration;
-    ///
-    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
-    /// let mut button = Button::new()?;
-    ///
-    /// button.set_down_handler(|is_pressed| {
-    ///     println!("Is 'down' pressed: {is_pressed}");
-    /// });
-    ///
-    /// loop {
-    ///     button.process();
-    ///
-    ///     println!("Is 'up' pressed: {}", button.is_up());
-    ///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
-    ///
-    ///     thread::sleep(Duration::from_millis(100));
-    /// }
-    /// # }
-    /// ```
-    pub fn process(&self) {
-        self.button_handler.borrow_mut().process()
-    }
-
-    /// Get all pressed buttons by name.
-    ///
-    /// ```no_run
-    /// use ev3dev_lang_rust::Button;
-    /// use std::thread;
-    /// use std::time::Duration;
-    ///
-    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
-    /// let button = Button::new()?;
-    ///
-    /// loop {
-    ///     button.process();
-    ///     println!("Pressed buttons: {:?}", button.get_pressed_buttons());
-    ///     thread::sleep(Duration::from_millis(100));
-    /// }
-    /// # }
-    /// ```
-    pub fn get_pressed_buttons(&self) -> HashSet<String> {
-        self.button_handler.borrow().get_pressed_buttons()
-    }
-
-    /// Set an event handler, that is called by `process()` if any button state changes.
-    /// Has a set of all pressed buttons as parameter.
-    ///
-    /// ```no_run
-    /// use ev3dev_lang_rust::Button;
-    /// use std::thread;
-    /// use std::time::Duration;
-    ///
-    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
-    /// let mut button = Button::new()?;
-    ///
-    /// button.set_change_handler(|pressed_buttons| {
-    ///     println!("pressed buttons: {:?}", pressed_buttons);
-    /// });
-    ///
-    /// loop {
-    ///     button.process();
-    ///     thread::sleep(Duration::from_millis(100));
-    /// }
-    /// # }
-    /// ```
-    pub fn set_change_handler(&mut self, handler: impl Fn(HashSet<String>) + 'static) {
-        self.button_handler
-            .borrow_mut()
-            .set_button_change_handler(Some(Box::new(handler)))
-    }
-
-    /// Removes the change event handler.
-    pub fn remove_change_handler(&mut self) {
-        self.button_handler
-            .borrow_mut()
-            .set_button_change_handler(None)
-    }
-
-    ev3_button_functions!(up);
-    ev3_button_functions!(down);
-    ev3_button_functions!(left);
-    ev3_button_functions!(right);
-    ev3_button_functions!(enter);
-    ev3_button_functions!(backspace);
-}
@@ -1,10 +1,11 @@
 /// Helper macro to create all necessary functions for a button
 #[macro_export]
 macro_rules! ev3_button_functions {
-    ($button_name:ident) => {
+    ($button_name:ident, $button_id:ident) => {
         paste! {
             #[doc = "Check if the `" $button_name "` button is pressed."]
-            #[doc = "```no_run"]
+            #[cfg_attr(feature = "ev3", doc = "```no_run")]
+            #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
             #[doc = "use ev3dev_lang_rust::Button;"]
             #[doc = "use std::thread;"]
             #[doc = "use std::time::Duration;"]
@@ -20,11 +21,12 @@ macro_rules! ev3_button_functions {
             #[doc = "# }"]
             #[doc = "```"]
             pub fn [<is_ $button_name>] (&self) -> bool {
-                self.button_handler.borrow().get_button_state(stringify!($button_name))
+                self.button_handler.borrow().get_button_state(ButtonId::$button_id)
             }
 
             #[doc = "Set an event handler, that is called by `process()` if the pressed state of the `" $button_name "` button changes."]
-            #[doc = "```no_run"]
+            #[cfg_attr(feature = "ev3", doc = "```no_run")]
+            #[cfg_attr(not(feature = "ev3"), doc = "```ignore")]
             #[doc = "use ev3dev_lang_rust::Button;"]
             #[doc = "use std::thread;"]
             #[doc = "use std::time::Duration;"]
@@ -45,14 +47,14 @@ macro_rules! ev3_button_functions {
             pub fn [<set_ $button_name _handler>](&mut self, handler: impl Fn(bool) + 'static) {
                 self.button_handler
                     .borrow_mut()
-                    .set_button_handler(stringify!($button_name), Some(Box::new(handler)));
+                    .set_button_handler(ButtonId::$button_id, Some(Box::new(handler)));
             }
 
             #[doc = "Removes the event handler of the `" $button_name "` button."]
             pub fn [<remove_ $button_name _handler>](&mut self) {
                 self.button_handler
                     .borrow_mut()
-                    .set_button_handler(stringify!($button_name), None);
+                    .set_button_handler(ButtonId::$button_id, None);
             }
         }
     };
@@ -0,0 +1,157 @@
+//! Filters to smooth noisy sensor values.
+//!
+//! # Example
+//! ```
+//! use ev3dev_lang_rust::filter::Filter;
+//!
+//! let mut filter = Filter::median(3);
+//! filter.update(10.0);
+//! filter.update(50.0);
+//! // The outlier 50 is removed by the median.
+//! assert_eq!(filter.update(12.0), 12.0);
+//! ```
+
+use std::collections::VecDeque;
+
+/// Median over a sliding window of the last values. Removes single outliers without delaying steps much.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MedianFilter {
+    window: usize,
+    values: VecDeque<f32>,
+}
+
+impl MedianFilter {
+    /// Create a filter over the last `window` values. A window of `0` is treated as `1`.
+    pub fn new(window: usize) -> Self {
+        MedianFilter {
+            window: window.max(1),
+            values: VecDeque::with_capacity(window.max(1)),
+        }
+    }
+
+    /// Returns the size of the sliding window.
+    pub fn get_window(&self) -> usize {
+        self.window
+    }
+
+    /// Adds a value and returns the median of the window.
+    /// Until the window is filled, the median of the values so far is returned.
+    pub fn update(&mut self, value: f32) -> f32 {
+        if self.values.len() >= self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+        self.get_value().unwrap_or(value)
+    }
+
+    /// Returns the median of the window or `None` if no value was added since the last reset.
+    /// For an even number of values the mean of the two middle values is returned.
+    pub fn get_value(&self) -> Option<f32> {
+        if self.values.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<f32> = self.values.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+
+        let middle = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            Some((sorted[middle - 1] + sorted[middle]) / 2.0)
+        } else {
+            Some(sorted[middle])
+        }
+    }
+
+    /// Discards all values.
+    pub fn reset(&mut self) {
+        self.values.clear();
+    }
+}
+
+/// Exponential moving average. Smooths noise with little computation, but delays steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExponentialFilter {
+    alpha: f32,
+    value: Option<f32>,
+}
+
+impl ExponentialFilter {
+    /// Create a filter with the smoothing factor `alpha` in the range `(0, 1]`.
+    /// Small values smooth stronger, `1` disables the filter. The factor is clamped to this range.
+    pub fn new(alpha: f32) -> Self {
+        ExponentialFilter {
+            alpha: alpha.clamp(f32::EPSILON, 1.0),
+            value: None,
+        }
+    }
+
+    /// Returns the smoothing factor.
+    pub fn get_alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Adds a value and returns the new average. The first value is returned unchanged.
+    pub fn update(&mut self, value: f32) -> f32 {
+        let average = match self.value {
+            Some(average) => average + self.alpha * (value - average),
+            None => value,
+        };
+        self.value = Some(average);
+        average
+    }
+
+    /// Returns the current average or `None` if no value was added since the last reset.
+    pub fn get_value(&self) -> Option<f32> {
+        self.value
+    }
+
+    /// Discards the current average.
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+}
+
+/// One of the available filters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Median over a sliding window.
+    Median(MedianFilter),
+    /// Exponential moving average.
+    Exponential(ExponentialFilter),
+}
+
+impl Filter {
+    /// Create a median filter over the last `window` values.
+    pub fn median(window: usize) -> Self {
+        Filter::Median(MedianFilter::new(window))
+    }
+
+    /// Create an exponential moving average with the smoothing factor `alpha`.
+    pub fn exponential(alpha: f32) -> Self {
+        Filter::Exponential(ExponentialFilter::new(alpha))
+    }
+
+    /// Adds a value and returns the filtered value.
+    pub fn update(&mut self, value: f32) -> f32 {
+        match self {
+            Filter::Median(filter) => filter.update(value),
+            Filter::Exponential(filter) => filter.update(value),
+        }
+    }
+
+    /// Returns the filtered value or `None` if no value was added since the last reset.
+    pub fn get_value(&self) -> Option<f32> {
+        match self {
+            Filter::Median(filter) => filter.get_value(),
+            Filter::Exponential(filter) => filter.get_value(),
+        }
+    }
+
+    /// Discards all values.
+    pub fn reset(&mut self) {
+        match self {
+            Filter::Median(filter) => filter.reset(),
+            Filter::Exponential(filter) => filter.reset(),
+        }
+    }
+}
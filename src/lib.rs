@@ -71,11 +71,19 @@ pub use port::{LegoPort, Port};
 pub mod motors;
 pub mod sensors;
 
+pub mod telemetry;
+
+pub mod filter;
+
+mod button_id;
+pub use button_id::{ButtonId, ButtonSet};
+mod button;
+pub mod button_events;
+pub use button::Button;
+
 #[cfg(feature = "ev3")]
 mod ev3;
 #[cfg(feature = "ev3")]
-pub use ev3::Button;
-#[cfg(feature = "ev3")]
 pub use ev3::Led;
 #[cfg(feature = "ev3")]
 mod port_constants {
@@ -129,7 +137,10 @@ pub mod sound;
 mod power_supply;
 pub use power_supply::PowerSupply;
 
+mod safety_guard;
+pub use safety_guard::SafetyGuard;
+
 #[cfg(feature = "screen")]
-mod screen;
+pub mod screen;
 #[cfg(feature = "screen")]
 pub use screen::Screen;
@@ -0,0 +1,287 @@
+//! Step-response identification and PID gain proposals for tacho motors.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::TachoMotor;
+use crate::{Ev3Error, Ev3Result};
+
+/// First order plus dead time model of a motor, identified from a duty cycle step.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProcessModel {
+    /// Steady state speed per duty cycle, in tacho counts per second per percent.
+    pub gain: f32,
+    /// Time constant of the speed response in seconds.
+    pub time_constant: f32,
+    /// Dead time of the speed response in seconds.
+    pub dead_time: f32,
+}
+
+impl ProcessModel {
+    /// Identify the model from a step response with the two point method.
+    ///
+    /// `samples` are `(time since the step, speed)` tuples, `step_duty_cycle` is the applied duty cycle.
+    /// The steady state speed is the mean of the last fifth of the samples.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::motors::ProcessModel;
+    /// use std::time::Duration;
+    ///
+    /// // Speed response of a motor with gain 10, time constant 0.1s and dead time 0.02s.
+    /// let samples: Vec<(Duration, i32)> = (0..200)
+    ///     .map(|i| {
+    ///         let t = i as f32 * 0.005;
+    ///         let speed = if t < 0.02 { 0.0 } else { 500.0 * (1.0 - (-(t - 0.02) / 0.1).exp()) };
+    ///         (Duration::from_secs_f32(t), speed.round() as i32)
+    ///     })
+    ///     .collect();
+    ///
+    /// let model = ProcessModel::from_step_response(&samples, 50).unwrap();
+    /// assert!((model.gain - 10.0).abs() < 0.1);
+    /// assert!((model.time_constant - 0.1).abs() < 0.01);
+    /// assert!((model.dead_time - 0.02).abs() < 0.01);
+    /// ```
+    pub fn from_step_response(
+        samples: &[(Duration, i32)],
+        step_duty_cycle: i32,
+    ) -> Ev3Result<ProcessModel> {
+        if step_duty_cycle == 0 || samples.len() < 10 {
+            return Err(Ev3Error::InternalError {
+                msg: "Step response needs a non zero step and at least 10 samples".to_owned(),
+            });
+        }
+
+        let tail = &samples[samples.len() - samples.len() / 5..];
+        let steady_state =
+            tail.iter().map(|(_, speed)| *speed as f32).sum::<f32>() / tail.len() as f32;
+
+        if steady_state.abs() < 1.0 {
+            return Err(Ev3Error::InternalError {
+                msg: "Motor did not move during the step response".to_owned(),
+            });
+        }
+
+        let not_settled = || Ev3Error::InternalError {
+            msg: "Step response did not settle".to_owned(),
+        };
+        let t28 = crossing_time(samples, 0.283 * steady_state).ok_or_else(not_settled)?;
+        let t63 = crossing_time(samples, 0.632 * steady_state).ok_or_else(not_settled)?;
+
+        let sample_interval = samples[samples.len() - 1].0.as_secs_f32() / samples.len() as f32;
+        let time_constant = (1.5 * (t63 - t28)).max(sample_interval);
+        let dead_time = (t63 - time_constant).max(0.0);
+
+        Ok(ProcessModel {
+            gain: steady_state / step_duty_cycle as f32,
+            time_constant,
+            dead_time,
+        })
+    }
+}
+
+/// Linear interpolated time at which the response first reaches `level`.
+fn crossing_time(samples: &[(Duration, i32)], level: f32) -> Option<f32> {
+    let reached = |speed: f32| {
+        if level >= 0.0 {
+            speed >= level
+        } else {
+            speed <= level
+        }
+    };
+
+    let mut previous: Option<(f32, f32)> = None;
+    for (time, speed) in samples {
+        let (t, y) = (time.as_secs_f32(), *speed as f32);
+        if reached(y) {
+            return Some(match previous {
+                Some((t0, y0)) if (y - y0).abs() > f32::EPSILON => {
+                    t0 + (level - y0) / (y - y0) * (t - t0)
+                }
+                _ => t,
+            });
+        }
+        previous = Some((t, y));
+    }
+    None
+}
+
+/// Gains of a PID controller in the units of the ev3dev `*_pid/K*` attributes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PidGains {
+    /// Proportional gain.
+    pub kp: f32,
+    /// Integral gain.
+    pub ki: f32,
+    /// Derivative gain.
+    pub kd: f32,
+}
+
+/// Result of an auto-tune run.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AutoTuneResult {
+    /// Identified motor model for the current load.
+    pub model: ProcessModel,
+    /// Proposed gains for the speed regulation PID.
+    pub speed_pid: PidGains,
+    /// Proposed gains for the position hold PID.
+    pub hold_pid: PidGains,
+}
+
+impl AutoTuneResult {
+    /// Writes the proposed gains to the `speed_pid` and `hold_pid` attributes of the motor.
+    /// The gains are rounded to integers, the driver rejects fractional values.
+    pub fn apply(&self, motor: &TachoMotor) -> Ev3Result<()> {
+        motor.set_speed_pid_kp(self.speed_pid.kp.round())?;
+        motor.set_speed_pid_ki(self.speed_pid.ki.round())?;
+        motor.set_speed_pid_kd(self.speed_pid.kd.round())?;
+        motor.set_hold_pid_kp(self.hold_pid.kp.round())?;
+        motor.set_hold_pid_ki(self.hold_pid.ki.round())?;
+        motor.set_hold_pid_kd(self.hold_pid.kd.round())
+    }
+}
+
+/// Proposes PID gains for the speed and hold loops of a motor with its current load.
+///
+/// The motor is driven with a duty cycle step forward and backward in `run-direct` mode,
+/// the speed response is identified as `ProcessModel` and the gains are calculated with the
+/// Ziegler–Nichols reaction curve rules (speed loop) and the SIMC rules for an integrating
+/// process (hold loop).
+///
+/// The controller gains are converted to attribute values with `gain_scale` and `control_period`.
+/// The defaults result in values of the same magnitude as the ev3dev defaults,
+/// but the proposals are only a starting point for manual fine tuning.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::motors::{AutoTuner, LargeMotor, MotorPort, TachoMotor};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let motor: TachoMotor = LargeMotor::get(MotorPort::OutA)?.into();
+///
+/// let result = AutoTuner::default().run(&motor)?;
+/// println!("Identified model: {:?}", result.model);
+/// println!("Speed PID: {:?}", result.speed_pid);
+///
+/// result.apply(&motor)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AutoTuner {
+    /// Duty cycle of the test step in percent.
+    pub step_duty_cycle: i32,
+    /// Duration of each test step.
+    pub step_duration: Duration,
+    /// Interval in which the speed is sampled.
+    pub sample_interval: Duration,
+    /// Factor between the controller gains (duty cycle percent per count) and the attribute values.
+    pub gain_scale: f32,
+    /// Period of the kernel control loop used to convert the integral and derivative gains.
+    pub control_period: Duration,
+}
+
+impl Default for AutoTuner {
+    fn default() -> Self {
+        AutoTuner {
+            step_duty_cycle: 50,
+            step_duration: Duration::from_millis(1000),
+            sample_interval: Duration::from_millis(5),
+            gain_scale: 1000.0,
+            control_period: Duration::from_millis(2),
+        }
+    }
+}
+
+impl AutoTuner {
+    /// Drive the motor through the test motions and propose gains. The gains are not applied.
+    pub fn run(&self, motor: &TachoMotor) -> Ev3Result<AutoTuneResult> {
+        let models = self
+            .measure(motor, self.step_duty_cycle)
+            .and_then(|forward| {
+                let backward = self.measure(motor, -self.step_duty_cycle)?;
+                Ok((forward, backward))
+            });
+
+        motor.set_duty_cycle_sp(0)?;
+        motor.stop()?;
+
+        let (forward, backward) = models?;
+        let model = ProcessModel {
+            gain: (forward.gain + backward.gain) / 2.0,
+            time_constant: (forward.time_constant + backward.time_constant) / 2.0,
+            dead_time: (forward.dead_time + backward.dead_time) / 2.0,
+        };
+
+        Ok(AutoTuneResult {
+            model,
+            speed_pid: self.speed_gains(&model),
+            hold_pid: self.hold_gains(&model),
+        })
+    }
+
+    /// Drive the motor through the test motions, propose gains and apply them.
+    pub fn run_and_apply(&self, motor: &TachoMotor) -> Ev3Result<AutoTuneResult> {
+        let result = self.run(motor)?;
+        result.apply(motor)?;
+        Ok(result)
+    }
+
+    /// Apply a duty cycle step to the resting motor and identify the speed response.
+    fn measure(&self, motor: &TachoMotor, duty_cycle: i32) -> Ev3Result<ProcessModel> {
+        motor.set_duty_cycle_sp(0)?;
+        motor.run_direct()?;
+        thread::sleep(Duration::from_millis(500));
+
+        let mut samples = Vec::new();
+        let start = Instant::now();
+        motor.set_duty_cycle_sp(duty_cycle)?;
+
+        while start.elapsed() < self.step_duration {
+            samples.push((start.elapsed(), motor.get_speed()?));
+            thread::sleep(self.sample_interval);
+        }
+
+        motor.set_duty_cycle_sp(0)?;
+
+        ProcessModel::from_step_response(&samples, duty_cycle)
+    }
+
+    /// The dead time is limited to the sample interval, as shorter delays cannot be measured.
+    fn dead_time(&self, model: &ProcessModel) -> f32 {
+        model.dead_time.max(self.sample_interval.as_secs_f32())
+    }
+
+    /// Converts ideal PID parameters to attribute values.
+    fn gains_from(&self, kp: f32, ti: f32, td: f32) -> PidGains {
+        let period = self.control_period.as_secs_f32();
+        PidGains {
+            kp: kp * self.gain_scale,
+            ki: if ti > 0.0 {
+                kp / ti * period * self.gain_scale
+            } else {
+                0.0
+            },
+            kd: kp * td / period * self.gain_scale,
+        }
+    }
+
+    /// Ziegler–Nichols reaction curve rules for the speed loop.
+    fn speed_gains(&self, model: &ProcessModel) -> PidGains {
+        let dead_time = self.dead_time(model);
+        let kp = 1.2 * model.time_constant / (model.gain.abs() * dead_time);
+        self.gains_from(kp, 2.0 * dead_time, 0.5 * dead_time)
+    }
+
+    /// SIMC rules for the hold loop, where the position integrates the speed.
+    fn hold_gains(&self, model: &ProcessModel) -> PidGains {
+        let dead_time = self.dead_time(model);
+        let closed_loop_time = dead_time;
+        let kp = 1.0 / (model.gain.abs() * (closed_loop_time + dead_time));
+        self.gains_from(
+            kp,
+            4.0 * (closed_loop_time + dead_time),
+            model.time_constant,
+        )
+    }
+}
@@ -0,0 +1,254 @@
+//! Homing routine and soft position limits for tacho motors.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::TachoMotor;
+use crate::sensors::TouchSensor;
+use crate::{Ev3Error, Ev3Result};
+
+/// Interval in which the homing routine checks its trigger.
+const HOMING_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The `stalled` flag is ignored for this time after starting the motor,
+/// because the speed regulation has not settled yet.
+const HOMING_STALL_GRACE: Duration = Duration::from_millis(200);
+
+/// Condition that ends the homing drive.
+#[derive(Debug, Clone)]
+pub enum HomingTrigger {
+    /// The motor has run into a hard stop and reports the `stalled` state.
+    Stall,
+    /// A touch sensor at the home position is pressed.
+    TouchSensor(TouchSensor),
+}
+
+impl HomingTrigger {
+    fn is_triggered(&self, motor: &TachoMotor) -> Ev3Result<bool> {
+        match self {
+            HomingTrigger::Stall => motor.is_stalled(),
+            HomingTrigger::TouchSensor(sensor) => sensor.get_pressed_state(),
+        }
+    }
+}
+
+/// Drive the motor with `speed_sp` towards the home position until the `trigger` fires,
+/// then stop the motor and set its position to `0`.
+///
+/// The sign of `speed_sp` selects the direction. Use a low speed to protect the mechanics.
+/// If the `timeout` is reached the motor is stopped and an error is returned.
+/// If the `timeout` is `None` it will wait an infinite time.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::motors::{home, HomingTrigger, MediumMotor, MotorPort, TachoMotor};
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let arm: TachoMotor = MediumMotor::get(MotorPort::OutA)?.into();
+///
+/// // Slowly drive backwards until the arm hits its end stop.
+/// home(&arm, -100, &HomingTrigger::Stall, Some(Duration::from_secs(10)))?;
+///
+/// assert_eq!(arm.get_position()?, 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn home(
+    motor: &TachoMotor,
+    speed_sp: i32,
+    trigger: &HomingTrigger,
+    timeout: Option<Duration>,
+) -> Ev3Result<()> {
+    motor.set_speed_sp(speed_sp)?;
+    motor.run_forever()?;
+
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed();
+
+        let triggered = match trigger {
+            HomingTrigger::Stall if elapsed < HOMING_STALL_GRACE => Ok(false),
+            _ => trigger.is_triggered(motor),
+        };
+        let triggered = match triggered {
+            Ok(triggered) => triggered,
+            Err(e) => {
+                motor.stop()?;
+                return Err(e);
+            }
+        };
+
+        if triggered {
+            break;
+        }
+
+        if let Some(duration) = timeout {
+            if elapsed >= duration {
+                motor.stop()?;
+                return Err(Ev3Error::InternalError {
+                    msg: "Homing did not finish within the timeout".to_owned(),
+                });
+            }
+        }
+
+        thread::sleep(HOMING_POLL_INTERVAL);
+    }
+
+    motor.stop()?;
+    motor.set_position(0)
+}
+
+/// Wrapper around a `TachoMotor` that keeps absolute position targets inside soft limits.
+///
+/// Positions are tacho counts relative to the home position, see `home()`.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::motors::{HomingTrigger, LimitedMotor, MediumMotor, MotorPort};
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let arm = LimitedMotor::new(MediumMotor::get(MotorPort::OutA)?, 0, 270)?;
+///
+/// arm.home(-100, &HomingTrigger::Stall, Some(Duration::from_secs(10)))?;
+///
+/// arm.get_motor().set_speed_sp(300)?;
+/// // The target is clamped to 270.
+/// arm.run_to_abs_pos(Some(400))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LimitedMotor {
+    motor: TachoMotor,
+    min_position: i32,
+    max_position: i32,
+}
+
+impl LimitedMotor {
+    /// Wrap a motor into a `LimitedMotor` with the range `[min_position, max_position]`.
+    ///
+    /// Returns an error if `min_position` is greater than `max_position`.
+    pub fn new(
+        motor: impl Into<TachoMotor>,
+        min_position: i32,
+        max_position: i32,
+    ) -> Ev3Result<LimitedMotor> {
+        let mut limited = LimitedMotor {
+            motor: motor.into(),
+            min_position,
+            max_position,
+        };
+        limited.set_limits(min_position, max_position)?;
+        Ok(limited)
+    }
+
+    /// Returns the wrapped motor.
+    pub fn get_motor(&self) -> &TachoMotor {
+        &self.motor
+    }
+
+    /// Consumes the wrapper and returns the wrapped motor.
+    pub fn into_motor(self) -> TachoMotor {
+        self.motor
+    }
+
+    /// Returns the soft limits as `(min_position, max_position)` tuple.
+    pub fn get_limits(&self) -> (i32, i32) {
+        (self.min_position, self.max_position)
+    }
+
+    /// Sets the soft limits.
+    ///
+    /// Returns an error if `min_position` is greater than `max_position`.
+    pub fn set_limits(&mut self, min_position: i32, max_position: i32) -> Ev3Result<()> {
+        if min_position > max_position {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Invalid soft limits [{min_position}, {max_position}]"),
+            });
+        }
+        self.min_position = min_position;
+        self.max_position = max_position;
+        Ok(())
+    }
+
+    /// Clamps the given absolute position to the soft limits.
+    pub fn clamp(&self, position: i32) -> i32 {
+        position.clamp(self.min_position, self.max_position)
+    }
+
+    /// Checks if the current position is inside the soft limits.
+    pub fn is_within_limits(&self) -> Ev3Result<bool> {
+        let position = self.motor.get_position()?;
+        Ok(position >= self.min_position && position <= self.max_position)
+    }
+
+    /// Run the homing routine on the wrapped motor. See `home()` for details.
+    pub fn home(
+        &self,
+        speed_sp: i32,
+        trigger: &HomingTrigger,
+        timeout: Option<Duration>,
+    ) -> Ev3Result<()> {
+        home(&self.motor, speed_sp, trigger, timeout)
+    }
+
+    /// Runs the motor to an absolute position specified by `position_sp`,
+    /// clamped to the soft limits.
+    ///
+    /// If `position_sp` is `None` the current `position_sp` of the motor is clamped and used.
+    pub fn run_to_abs_pos(&self, position_sp: Option<i32>) -> Ev3Result<()> {
+        let position = match position_sp {
+            Some(p) => p,
+            None => self.motor.get_position_sp()?,
+        };
+        self.motor.run_to_abs_pos(Some(self.clamp(position)))
+    }
+
+    /// Runs the motor to a position relative to the current position value.
+    ///
+    /// The resulting absolute target is clamped to the soft limits.
+    /// If `position_sp` is `None` the current `position_sp` of the motor is used.
+    pub fn run_to_rel_pos(&self, position_sp: Option<i32>) -> Ev3Result<()> {
+        let offset = match position_sp {
+            Some(p) => p,
+            None => self.motor.get_position_sp()?,
+        };
+        let target = self.motor.get_position()?.saturating_add(offset);
+        self.motor.run_to_abs_pos(Some(self.clamp(target)))
+    }
+
+    /// Runs the motor with `speed_sp` in its direction until the soft limit is reached.
+    ///
+    /// Instead of `run-forever` this issues `run-to-abs-pos` with the limit as target,
+    /// so the motor is stopped by `stop_action` at the limit.
+    /// Returns an error if the motor is already at or beyond the limit in the direction of `speed_sp`.
+    pub fn run_forever(&self) -> Ev3Result<()> {
+        let speed_sp = self.motor.get_speed_sp()?;
+        let position = self.motor.get_position()?;
+
+        let target = if speed_sp > 0 {
+            self.max_position
+        } else if speed_sp < 0 {
+            self.min_position
+        } else {
+            return self.motor.run_forever();
+        };
+
+        if (speed_sp > 0 && position >= target) || (speed_sp < 0 && position <= target) {
+            return Err(Ev3Error::InternalError {
+                msg: format!(
+                    "Cannot run beyond soft limit {target} from position {position} with speed {speed_sp}"
+                ),
+            });
+        }
+
+        self.motor.run_to_abs_pos(Some(target))
+    }
+
+    /// Stop any of the run commands before they are complete using the command specified by `stop_action`.
+    pub fn stop(&self) -> Ev3Result<()> {
+        self.motor.stop()
+    }
+}
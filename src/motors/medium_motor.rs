@@ -1,4 +1,4 @@
-use super::MotorPort;
+use super::{MotorPort, PositionValue, SpeedValue};
 use crate::{wait, Attribute, Device, Driver, Ev3Error, Ev3Result};
 use std::time::Duration;
 
@@ -16,6 +16,15 @@ pub use self::medium_motor::MediumMotor;
 mod tacho_motor;
 pub use self::tacho_motor::TachoMotor;
 
+mod units;
+pub use self::units::{PositionValue, SpeedValue};
+
+mod autotune;
+pub use self::autotune::{AutoTuneResult, AutoTuner, PidGains, ProcessModel};
+
+mod limited_motor;
+pub use self::limited_motor::{home, HomingTrigger, LimitedMotor};
+
 use crate::{port_constants, Ev3Result, LegoPort, Port};
 
 /// EV3 ports `outA` to `outD`
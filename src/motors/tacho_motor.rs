@@ -4,9 +4,9 @@
 
 use std::time::Duration;
 
-use crate::{Ev3Error, Ev3Result};
+use crate::{Attribute, Device, Ev3Error, Ev3Result};
 
-use super::{LargeMotor, MediumMotor, MotorPort};
+use super::{LargeMotor, MediumMotor, MotorPort, PositionValue, SpeedValue};
 
 #[derive(Debug, Clone)]
 enum TachoMotorInner {
@@ -751,6 +751,69 @@ impl TachoMotor {
         }
     }
 
+    /// Converts a `SpeedValue` to tacho counts per second, the unit of `speed_sp`.
+    pub fn speed_to_counts(&self, speed: SpeedValue) -> Ev3Result<i32> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.speed_to_counts(speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.speed_to_counts(speed),
+        }
+    }
+
+    /// Converts a `PositionValue` to tacho counts, the unit of `position_sp`.
+    pub fn position_to_counts(&self, position: PositionValue) -> Ev3Result<i32> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.position_to_counts(position),
+            TachoMotorInner::MediumMotor { ref motor } => motor.position_to_counts(position),
+        }
+    }
+
+    /// Sets the target speed used for all run-* commands except run-direct.
+    pub fn set_speed(&self, speed: SpeedValue) -> Ev3Result<()> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.set_speed(speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.set_speed(speed),
+        }
+    }
+
+    /// Causes the motor to run with the given `speed` until another command is sent.
+    pub fn run_forever_at(&self, speed: SpeedValue) -> Ev3Result<()> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.run_forever_at(speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.run_forever_at(speed),
+        }
+    }
+
+    /// Run the motor with the given `speed` for the amount of time specified in `time_sp`
+    ///
+    /// and then stops the motor using the command specified by `stop_action`.
+    pub fn run_timed_at(&self, time_sp: Duration, speed: SpeedValue) -> Ev3Result<()> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.run_timed_at(time_sp, speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.run_timed_at(time_sp, speed),
+        }
+    }
+
+    /// Runs the motor with the given `speed` to an absolute `position`
+    ///
+    /// and then stops the motor using the command specified in `stop_action`.
+    /// `PositionValue::Millimeters` targets are clamped to the range `0` to `full_travel_count`.
+    pub fn run_to_abs_pos_at(&self, position: PositionValue, speed: SpeedValue) -> Ev3Result<()> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.run_to_abs_pos_at(position, speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.run_to_abs_pos_at(position, speed),
+        }
+    }
+
+    /// Runs the motor with the given `speed` to a `position` relative to the current position value
+    ///
+    /// and then stops the motor using the command specified in `stop_action`.
+    pub fn run_to_rel_pos_at(&self, position: PositionValue, speed: SpeedValue) -> Ev3Result<()> {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.run_to_rel_pos_at(position, speed),
+            TachoMotorInner::MediumMotor { ref motor } => motor.run_to_rel_pos_at(position, speed),
+        }
+    }
+
     /// Stop any of the run commands before they are complete using the command specified by `stop_action`.
     pub fn stop(&self) -> Ev3Result<()> {
         match self.inner {
@@ -940,6 +1003,15 @@ impl TachoMotor {
     }
 }
 
+impl Device for TachoMotor {
+    fn get_attribute(&self, name: &str) -> Attribute {
+        match self.inner {
+            TachoMotorInner::LargeMotor { ref motor } => motor.get_attribute(name),
+            TachoMotorInner::MediumMotor { ref motor } => motor.get_attribute(name),
+        }
+    }
+}
+
 impl From<LargeMotor> for TachoMotor {
     fn from(motor: LargeMotor) -> Self {
         Self {
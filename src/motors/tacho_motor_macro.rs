@@ -540,6 +540,95 @@ macro_rules! tacho_motor {
             self.set_command(Self::COMMAND_RUN_TIMED)
         }
 
+        /// Converts a `SpeedValue` to tacho counts per second, the unit of `speed_sp`.
+        pub fn speed_to_counts(&self, speed: SpeedValue) -> Ev3Result<i32> {
+            speed.to_counts_per_second(|| self.get_count_per_rot(), || self.get_max_speed())
+        }
+
+        /// Converts a `PositionValue` to tacho counts, the unit of `position_sp`.
+        pub fn position_to_counts(&self, position: PositionValue) -> Ev3Result<i32> {
+            position.to_counts(|| self.get_count_per_rot(), || self.get_count_per_m())
+        }
+
+        /// Sets the target speed used for all run-* commands except run-direct.
+        ///
+        /// # Examples
+        ///
+        /// ```no_run
+        /// use ev3dev_lang_rust::motors::{LargeMotor, SpeedValue};
+        ///
+        /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+        /// // Init a tacho motor.
+        /// let motor = LargeMotor::find()?;
+        ///
+        /// // Run at half of the maximum speed.
+        /// motor.set_speed(SpeedValue::Percent(50.0))?;
+        /// motor.run_forever()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn set_speed(&self, speed: SpeedValue) -> Ev3Result<()> {
+            self.set_speed_sp(self.speed_to_counts(speed)?)
+        }
+
+        /// Causes the motor to run with the given `speed` until another command is sent.
+        pub fn run_forever_at(&self, speed: SpeedValue) -> Ev3Result<()> {
+            self.set_speed(speed)?;
+            self.run_forever()
+        }
+
+        /// Run the motor with the given `speed` for the amount of time specified in `time_sp`
+        /// and then stops the motor using the command specified by `stop_action`.
+        pub fn run_timed_at(&self, time_sp: Duration, speed: SpeedValue) -> Ev3Result<()> {
+            self.set_speed(speed)?;
+            self.run_timed(Some(time_sp))
+        }
+
+        /// Runs the motor with the given `speed` to an absolute `position`
+        /// and then stops the motor using the command specified in `stop_action`.
+        ///
+        /// `PositionValue::Millimeters` targets are clamped to the range `0` to `full_travel_count`.
+        ///
+        /// # Examples
+        ///
+        /// ```no_run
+        /// use ev3dev_lang_rust::motors::{LargeMotor, PositionValue, SpeedValue};
+        ///
+        /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+        /// // Init a tacho motor.
+        /// let motor = LargeMotor::find()?;
+        ///
+        /// // Turn to 90 degrees with 30 rotations per minute.
+        /// motor.run_to_abs_pos_at(PositionValue::Degrees(90.0), SpeedValue::Rpm(30.0))?;
+        /// motor.wait_until_not_moving(None);
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn run_to_abs_pos_at(
+            &self,
+            position: PositionValue,
+            speed: SpeedValue,
+        ) -> Ev3Result<()> {
+            let mut counts = self.position_to_counts(position)?;
+            if let PositionValue::Millimeters(_) = position {
+                counts = counts.clamp(0, self.get_full_travel_count()?);
+            }
+            self.set_speed(speed)?;
+            self.run_to_abs_pos(Some(counts))
+        }
+
+        /// Runs the motor with the given `speed` to a `position` relative to the current position value
+        /// and then stops the motor using the command specified in `stop_action`.
+        pub fn run_to_rel_pos_at(
+            &self,
+            position: PositionValue,
+            speed: SpeedValue,
+        ) -> Ev3Result<()> {
+            let counts = self.position_to_counts(position)?;
+            self.set_speed(speed)?;
+            self.run_to_rel_pos(Some(counts))
+        }
+
         /// Stop any of the run commands before they are complete using the command specified by `stop_action`.
         pub fn stop(&self) -> Ev3Result<()> {
             self.set_command(Self::COMMAND_STOP)
@@ -0,0 +1,90 @@
+//! Unit-aware speed and position values for tacho motors.
+
+use crate::Ev3Result;
+
+/// A motor speed in one of several units.
+///
+/// The motor attributes `speed_sp` and `speed` are measured in tacho counts per second.
+/// Use `to_counts_per_second()` or the `run_*_at` methods of the motors to convert a value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpeedValue {
+    /// Percent of the motor's `max_speed`, in the range -100 to 100.
+    Percent(f32),
+    /// Rotations per minute.
+    Rpm(f32),
+    /// Degrees per second.
+    DegreesPerSecond(f32),
+    /// Tacho counts per second, the native unit of `speed_sp`.
+    CountsPerSecond(i32),
+}
+
+impl SpeedValue {
+    /// Converts the speed to tacho counts per second.
+    ///
+    /// `count_per_rot` and `max_speed` are only called by the variants that need them,
+    /// so linear actuators without `count_per_rot` can still use `Percent` and `CountsPerSecond`.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::motors::SpeedValue;
+    ///
+    /// let counts = SpeedValue::Rpm(60.0).to_counts_per_second(|| Ok(360), || Ok(1050));
+    /// assert_eq!(counts.unwrap(), 360);
+    /// ```
+    pub fn to_counts_per_second<R, M>(self, count_per_rot: R, max_speed: M) -> Ev3Result<i32>
+    where
+        R: FnOnce() -> Ev3Result<i32>,
+        M: FnOnce() -> Ev3Result<i32>,
+    {
+        let counts = match self {
+            SpeedValue::Percent(percent) => percent / 100.0 * max_speed()? as f32,
+            SpeedValue::Rpm(rpm) => rpm / 60.0 * count_per_rot()? as f32,
+            SpeedValue::DegreesPerSecond(degrees) => degrees / 360.0 * count_per_rot()? as f32,
+            SpeedValue::CountsPerSecond(counts) => return Ok(counts),
+        };
+        Ok(counts.round() as i32)
+    }
+}
+
+/// A motor position in one of several units.
+///
+/// The motor attributes `position_sp` and `position` are measured in tacho counts.
+/// Use `to_counts()` or the `run_*_at` methods of the motors to convert a value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PositionValue {
+    /// Degrees of the motor shaft (rotation motors only).
+    Degrees(f32),
+    /// Full rotations of the motor shaft (rotation motors only).
+    Rotations(f32),
+    /// Millimetres of travel (linear motors only), based on `count_per_m`.
+    Millimeters(f32),
+    /// Tacho counts, the native unit of `position_sp`.
+    Counts(i32),
+}
+
+impl PositionValue {
+    /// Converts the position to tacho counts.
+    ///
+    /// `count_per_rot` and `count_per_m` are only called by the variants that need them.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::motors::PositionValue;
+    ///
+    /// let counts = PositionValue::Rotations(1.5).to_counts(|| Ok(360), || Ok(0));
+    /// assert_eq!(counts.unwrap(), 540);
+    /// ```
+    pub fn to_counts<R, M>(self, count_per_rot: R, count_per_m: M) -> Ev3Result<i32>
+    where
+        R: FnOnce() -> Ev3Result<i32>,
+        M: FnOnce() -> Ev3Result<i32>,
+    {
+        let counts = match self {
+            PositionValue::Degrees(degrees) => degrees / 360.0 * count_per_rot()? as f32,
+            PositionValue::Rotations(rotations) => rotations * count_per_rot()? as f32,
+            PositionValue::Millimeters(mm) => mm / 1000.0 * count_per_m()? as f32,
+            PositionValue::Counts(counts) => return Ok(counts),
+        };
+        Ok(counts.round() as i32)
+    }
+}
@@ -0,0 +1,270 @@
+//! Opt-in guard that brings all actuators into a safe state when the program ends.
+
+use std::fs;
+use std::os::unix::io::RawFd;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread;
+
+use crate::driver::DRIVER_PATH;
+use crate::motors::TachoMotor;
+use crate::{Attribute, Ev3Error, Ev3Result};
+
+/// Set while a `SafetyGuard` is alive. The panic hook and the signal watcher only act if this is `true`.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Write end of the self-pipe that is used to forward signals to the watcher thread.
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+/// Ensures that the panic hook is only registered once per process.
+static PANIC_HOOK: Once = Once::new();
+
+/// Set once the signal handlers are registered. Registration is retried by the next `install()` if it failed.
+static SIGNAL_HANDLERS: Mutex<bool> = Mutex::new(false);
+
+/// Sensor modes that should be restored on shutdown, as `(sensor name, mode)` tuples.
+static SENSOR_MODES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Stops all motors when the program exits, panics or receives `SIGINT` / `SIGTERM`.
+///
+/// Without this guard motors keep running with their last setpoints if the program terminates unexpectedly.
+/// The shutdown routine iterates all connected tacho, dc and servo motors and issues `stop` and `reset`
+/// (`float` for servo motors), resets the brick leds to green and optionally restores the sensor modes
+/// that were active when the guard was installed.
+///
+/// The shutdown routine runs when the guard is dropped, from a panic hook and when one of the signals
+/// is received. After handling a signal the process is terminated with the default signal action.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::motors::{LargeMotor, MotorPort};
+/// use ev3dev_lang_rust::SafetyGuard;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// // Keep the guard alive until the end of `main`.
+/// let _guard = SafetyGuard::install()?.restore_sensor_modes()?;
+///
+/// let motor = LargeMotor::get(MotorPort::OutA)?;
+/// motor.set_speed_sp(500)?;
+/// motor.run_forever()?;
+///
+/// // A panic or Ctrl+C from here on stops the motor.
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SafetyGuard {
+    _private: (),
+}
+
+impl SafetyGuard {
+    /// Install the guard. Registers the panic hook and the signal handlers on first use.
+    ///
+    /// Returns an error if another `SafetyGuard` is currently installed or the signal handlers
+    /// could not be registered. In the latter case the next call tries again.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::SafetyGuard;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let guard = SafetyGuard::install()?;
+    /// assert!(SafetyGuard::install().is_err());
+    ///
+    /// // Dropping the guard runs the shutdown routine, afterwards it can be installed again.
+    /// drop(guard);
+    /// let _guard = SafetyGuard::install()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn install() -> Ev3Result<SafetyGuard> {
+        if ACTIVE.swap(true, Ordering::SeqCst) {
+            return Err(Ev3Error::InternalError {
+                msg: "A SafetyGuard is already installed".to_owned(),
+            });
+        }
+
+        PANIC_HOOK.call_once(install_panic_hook);
+
+        let result = {
+            let mut installed = SIGNAL_HANDLERS.lock().unwrap_or_else(|e| e.into_inner());
+            if *installed {
+                Ok(())
+            } else {
+                let result = install_signal_handlers();
+                *installed = result.is_ok();
+                result
+            }
+        };
+
+        if let Err(e) = result {
+            ACTIVE.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+
+        Ok(SafetyGuard { _private: () })
+    }
+
+    /// Remember the current mode of every connected sensor and restore it on shutdown.
+    pub fn restore_sensor_modes(self) -> Ev3Result<SafetyGuard> {
+        let mut modes = Vec::new();
+
+        for path in fs::read_dir(Path::new(DRIVER_PATH).join("lego-sensor"))? {
+            let file_name = path?.file_name();
+            if let Some(name) = file_name.to_str() {
+                let mode = Attribute::from_sys_class("lego-sensor", name, "mode")?.get()?;
+                modes.push((name.to_owned(), mode));
+            }
+        }
+
+        *SENSOR_MODES.lock().unwrap_or_else(|e| e.into_inner()) = modes;
+
+        Ok(self)
+    }
+
+    /// Run the shutdown routine immediately without uninstalling the guard.
+    pub fn stop_all(&self) {
+        shutdown();
+    }
+}
+
+impl Drop for SafetyGuard {
+    fn drop(&mut self) {
+        shutdown();
+        SENSOR_MODES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Brings all actuators into a safe state. Errors are ignored, as this runs during panics and signals.
+fn shutdown() {
+    if let Ok(motors) = TachoMotor::list() {
+        for motor in motors {
+            let _ = motor.stop();
+            let _ = motor.reset();
+        }
+    }
+
+    for_each_device("dc-motor", |name| {
+        if let Ok(command) = Attribute::from_sys_class("dc-motor", name, "command") {
+            let _ = command.set_str_slice("stop");
+        }
+    });
+
+    for_each_device("servo-motor", |name| {
+        if let Ok(command) = Attribute::from_sys_class("servo-motor", name, "command") {
+            let _ = command.set_str_slice("float");
+        }
+    });
+
+    #[cfg(feature = "ev3")]
+    if let Ok(led) = crate::Led::new() {
+        let _ = led.set_color(crate::Led::COLOR_GREEN);
+    }
+
+    let modes = SENSOR_MODES
+        .lock()
+        .map(|modes| modes.clone())
+        .unwrap_or_default();
+    for (name, mode) in modes {
+        if let Ok(attribute) = Attribute::from_sys_class("lego-sensor", &name, "mode") {
+            let _ = attribute.set_str_slice(&mode);
+        }
+    }
+}
+
+/// Calls `f` with the name of every device of the given class. Missing classes are skipped.
+fn for_each_device<F>(class_name: &str, f: F)
+where
+    F: Fn(&str),
+{
+    if let Ok(paths) = fs::read_dir(Path::new(DRIVER_PATH).join(class_name)) {
+        for path in paths.flatten() {
+            if let Some(name) = path.file_name().to_str() {
+                f(name);
+            }
+        }
+    }
+}
+
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if ACTIVE.load(Ordering::SeqCst) {
+            shutdown();
+        }
+        previous(info);
+    }));
+}
+
+/// Signal handler. Only forwards the signal number to the watcher thread, which is async-signal-safe.
+extern "C" fn handle_signal(signal: libc::c_int) {
+    let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        let byte = signal as u8;
+        unsafe {
+            libc::write(fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+    }
+}
+
+fn install_signal_handlers() -> Ev3Result<()> {
+    // The pipe and the watcher thread are kept if a previous attempt failed later on.
+    if SIGNAL_PIPE.load(Ordering::SeqCst) < 0 {
+        let mut fds: [RawFd; 2] = [-1; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let [read_fd, write_fd] = fds;
+
+        let spawned = thread::Builder::new()
+            .name("safety-guard".to_owned())
+            .spawn(move || watch_signals(read_fd));
+        if let Err(e) = spawned {
+            unsafe {
+                libc::close(read_fd);
+                libc::close(write_fd);
+            }
+            return Err(e.into());
+        }
+        SIGNAL_PIPE.store(write_fd, Ordering::SeqCst);
+    }
+
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        let handler = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits for a forwarded signal, runs the shutdown routine and terminates the process with the default action.
+fn watch_signals(read_fd: RawFd) {
+    let mut byte = 0u8;
+    loop {
+        let n = unsafe { libc::read(read_fd, &mut byte as *mut u8 as *mut libc::c_void, 1) };
+        if n == 1 {
+            break;
+        }
+        if n == -1 && std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+            continue;
+        }
+        return;
+    }
+
+    let signal = byte as libc::c_int;
+    if ACTIVE.load(Ordering::SeqCst) {
+        shutdown();
+    }
+
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
@@ -0,0 +1,314 @@
+//! Outputs of the `Screen`: the Linux framebuffer or headless PNG rendering.
+
+use super::{to_monochrome, Dithering, PixelFormat, BLACK, WHITE};
+use crate::{Ev3Error, Ev3Result};
+use framebuffer::Framebuffer;
+use image::{ImageFormat, RgbImage};
+use std::env;
+use std::fs;
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Default framebuffer device of the display.
+pub const FRAMEBUFFER_PATH: &str = "/dev/fb0";
+
+/// Horizontal resolution of the EV3 display, used by headless screens.
+pub const EV3_XRES: u32 = 178;
+/// Vertical resolution of the EV3 display, used by headless screens.
+pub const EV3_YRES: u32 = 128;
+
+/// Environment variable to select the backend with `ScreenBackend::from_env()`.
+pub const SCREEN_BACKEND_ENV: &str = "EV3DEV_SCREEN";
+
+/// Where a headless screen stores its frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadlessOutput {
+    /// Keep the last frame in memory only, see `Screen::get_rendered_frame()`.
+    Memory,
+    /// Overwrite the PNG file on each update.
+    PngFile(PathBuf),
+    /// Write each update to a new numbered file `frame-00000.png`, `frame-00001.png`, ... in the directory.
+    PngSequence(PathBuf),
+}
+
+/// Output of a `Screen`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreenBackend {
+    /// Draw to the framebuffer device at the given path.
+    Framebuffer(PathBuf),
+    /// Render black and white frames with the EV3 resolution without display hardware.
+    Headless(HeadlessOutput),
+}
+
+impl Default for ScreenBackend {
+    fn default() -> Self {
+        ScreenBackend::Framebuffer(PathBuf::from(FRAMEBUFFER_PATH))
+    }
+}
+
+impl ScreenBackend {
+    /// Parses a backend description:
+    /// `memory`, `png:<file>`, `png-sequence:<directory>` or the absolute path of a framebuffer device.
+    ///
+    /// Fails for any other description.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::{HeadlessOutput, ScreenBackend};
+    /// use std::path::PathBuf;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// assert_eq!(
+    ///     ScreenBackend::parse("png:/tmp/screen.png")?,
+    ///     ScreenBackend::Headless(HeadlessOutput::PngFile(PathBuf::from("/tmp/screen.png")))
+    /// );
+    /// assert_eq!(ScreenBackend::parse("/dev/fb1")?, ScreenBackend::Framebuffer(PathBuf::from("/dev/fb1")));
+    /// assert!(ScreenBackend::parse("pgn:/tmp/screen.png").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(description: &str) -> Ev3Result<ScreenBackend> {
+        if description == "memory" {
+            Ok(ScreenBackend::Headless(HeadlessOutput::Memory))
+        } else if let Some(path) = description.strip_prefix("png:") {
+            Ok(ScreenBackend::Headless(HeadlessOutput::PngFile(
+                PathBuf::from(path),
+            )))
+        } else if let Some(path) = description.strip_prefix("png-sequence:") {
+            Ok(ScreenBackend::Headless(HeadlessOutput::PngSequence(
+                PathBuf::from(path),
+            )))
+        } else if description.starts_with('/') {
+            Ok(ScreenBackend::Framebuffer(PathBuf::from(description)))
+        } else {
+            Err(Ev3Error::InternalError {
+                msg: format!("Unknown screen backend '{description}'"),
+            })
+        }
+    }
+
+    /// Reads the backend from the `EV3DEV_SCREEN` environment variable, see `parse()`.
+    /// Defaults to the framebuffer `/dev/fb0` if the variable is not set.
+    /// Fails if the variable holds an unknown backend.
+    pub fn from_env() -> Ev3Result<ScreenBackend> {
+        match env::var(SCREEN_BACKEND_ENV) {
+            Ok(description) if !description.is_empty() => ScreenBackend::parse(&description),
+            _ => Ok(ScreenBackend::default()),
+        }
+    }
+}
+
+/// Framebuffer with the state of partial updates and page flipping.
+#[derive(Debug)]
+pub struct FramebufferOutput {
+    pub path: PathBuf,
+    pub buffer: Framebuffer,
+    pub format: PixelFormat,
+    pub partial_updates: bool,
+    pub double_buffered: bool,
+    visible_page: usize,
+    /// Last frame written to each framebuffer page.
+    pages: [Option<Vec<u8>>; 2],
+}
+
+impl FramebufferOutput {
+    pub fn open(path: PathBuf) -> Ev3Result<Self> {
+        let buffer = Framebuffer::new(&path)?;
+        let format = PixelFormat::from_screen_info(
+            &buffer.device,
+            &buffer.var_screen_info,
+            &buffer.fix_screen_info,
+        )?;
+
+        Ok(FramebufferOutput {
+            path,
+            buffer,
+            format,
+            partial_updates: true,
+            double_buffered: false,
+            visible_page: 0,
+            pages: [None, None],
+        })
+    }
+
+    pub fn invalidate(&mut self) {
+        self.pages = [None, None];
+    }
+
+    pub fn set_double_buffering(&mut self, double_buffered: bool) -> Ev3Result<()> {
+        let yres = self.buffer.var_screen_info.yres;
+        if double_buffered && self.buffer.var_screen_info.yres_virtual < 2 * yres {
+            let mut info = self.buffer.var_screen_info.clone();
+            info.yres_virtual = 2 * yres;
+            Framebuffer::put_var_screeninfo(&self.buffer.device, &info)?;
+
+            // The memory mapping has to grow with the virtual resolution.
+            self.buffer = Framebuffer::new(&self.path)?;
+            if self.buffer.var_screen_info.yres_virtual < 2 * yres {
+                return Err(Ev3Error::InternalError {
+                    msg: "Framebuffer does not support double buffering".to_owned(),
+                });
+            }
+        }
+
+        if !double_buffered && self.visible_page != 0 {
+            self.pan_to(0)?;
+        }
+
+        self.double_buffered = double_buffered;
+        self.invalidate();
+        Ok(())
+    }
+
+    /// Shows the given framebuffer page.
+    fn pan_to(&mut self, page: usize) -> Ev3Result<()> {
+        self.buffer.var_screen_info.yoffset = page as u32 * self.buffer.var_screen_info.yres;
+        Framebuffer::pan_display(&self.buffer.device, &self.buffer.var_screen_info)?;
+        self.visible_page = page;
+        Ok(())
+    }
+
+    /// Encodes the image and writes the parts of the frame that differ from the content of the target page.
+    pub fn update(&mut self, image: &RgbImage, dithering: Dithering) -> Ev3Result<()> {
+        let line_length = self.buffer.fix_screen_info.line_length as usize;
+        let frame = self.format.encode(image, dithering, line_length);
+
+        let page_size = line_length * self.buffer.var_screen_info.yres as usize;
+        if frame.len() != page_size {
+            return Err(Ev3Error::InternalError {
+                msg: format!(
+                    "Frame of {} bytes does not match the framebuffer page of {page_size} bytes",
+                    frame.len()
+                ),
+            });
+        }
+
+        let page = if self.double_buffered {
+            if self.pages[self.visible_page].as_ref() == Some(&frame) {
+                return Ok(());
+            }
+            1 - self.visible_page
+        } else {
+            0
+        };
+
+        let previous = self.pages[page].as_deref().filter(|_| self.partial_updates);
+        if let Some((rows, columns)) = changed_region(previous, &frame, line_length) {
+            let offset = page * page_size;
+            for row in rows {
+                let start = row * line_length;
+                let (begin, end) = (start + columns.start, start + columns.end);
+                self.buffer.frame[offset + begin..offset + end].copy_from_slice(&frame[begin..end]);
+            }
+        }
+
+        if self.double_buffered {
+            self.pan_to(page)?;
+        }
+        self.pages[page] = Some(frame);
+        Ok(())
+    }
+}
+
+/// Returns the rows and the byte columns that differ between both frames, or `None` if they are equal.
+/// Without a previous frame, the whole frame is returned.
+fn changed_region(
+    previous: Option<&[u8]>,
+    frame: &[u8],
+    line_length: usize,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let rows = frame.len() / line_length.max(1);
+    let previous = match previous {
+        Some(previous) if previous.len() == frame.len() => previous,
+        _ => return Some((0..rows, 0..line_length)),
+    };
+
+    let mut region: Option<(Range<usize>, Range<usize>)> = None;
+    for row in 0..rows {
+        let line = row * line_length..(row + 1) * line_length;
+        let (old, new) = (&previous[line.clone()], &frame[line]);
+        let first = match old.iter().zip(new).position(|(a, b)| a != b) {
+            Some(first) => first,
+            None => continue,
+        };
+        let last = line_length
+            - old
+                .iter()
+                .zip(new)
+                .rev()
+                .position(|(a, b)| a != b)
+                .unwrap_or(0);
+
+        region = Some(match region {
+            Some((rows, columns)) => (
+                rows.start..row + 1,
+                columns.start.min(first)..columns.end.max(last),
+            ),
+            None => (row..row + 1, first..last),
+        });
+    }
+    region
+}
+
+/// Renders frames as black and white images like the 1-bpp EV3 display.
+#[derive(Debug)]
+pub struct HeadlessRenderer {
+    pub output: HeadlessOutput,
+    pub frame: Option<RgbImage>,
+    pub format: PixelFormat,
+    frame_count: usize,
+}
+
+impl HeadlessRenderer {
+    pub fn new(output: HeadlessOutput) -> Ev3Result<Self> {
+        if let HeadlessOutput::PngSequence(directory) = &output {
+            fs::create_dir_all(directory)?;
+        }
+        Ok(HeadlessRenderer {
+            output,
+            frame: None,
+            format: PixelFormat::Monochrome {
+                set_is_white: false,
+            },
+            frame_count: 0,
+        })
+    }
+
+    /// Converts the visible part of the image to black and white and writes it to the output.
+    pub fn update(&mut self, image: &RgbImage, dithering: Dithering) -> Ev3Result<()> {
+        let bitmap = to_monochrome(image, dithering);
+        let (width, height) = (EV3_XRES.min(image.width()), EV3_YRES.min(image.height()));
+        let frame = RgbImage::from_fn(width, height, |x, y| {
+            if bitmap.get_pixel(x, y) {
+                BLACK
+            } else {
+                WHITE
+            }
+        });
+
+        match &self.output {
+            HeadlessOutput::Memory => {}
+            HeadlessOutput::PngFile(path) => fs::write(path, encode_png(&frame)?)?,
+            HeadlessOutput::PngSequence(directory) => {
+                let path = directory.join(format!("frame-{:05}.png", self.frame_count));
+                fs::write(path, encode_png(&frame)?)?;
+            }
+        }
+
+        self.frame_count += 1;
+        self.frame = Some(frame);
+        Ok(())
+    }
+}
+
+/// Encodes an image as PNG.
+pub fn encode_png(image: &RgbImage) -> Ev3Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|err| Ev3Error::InternalError {
+            msg: format!("Could not encode PNG: {err}"),
+        })?;
+    Ok(png)
+}
@@ -0,0 +1,431 @@
+//! Text console with scrollback on the screen.
+
+use super::{draw, Font, Screen, BLACK, WHITE};
+use crate::{Ev3Error, Ev3Result};
+use image::RgbImage;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+
+/// Number of lines kept by default, including the visible lines.
+const DEFAULT_SCROLLBACK: usize = 200;
+
+/// A character with its colors mapped to black and white.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Cell {
+    c: char,
+    black_text: bool,
+    black_background: bool,
+}
+
+/// Text attributes set by ANSI escape sequences.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Style {
+    black_text: bool,
+    black_background: bool,
+    reverse: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            black_text: true,
+            black_background: false,
+            reverse: false,
+        }
+    }
+}
+
+impl Style {
+    /// Maps an ANSI color (0 to 7, 8 to 15 for bright colors) to black (`true`) or white.
+    /// Black, red, blue, magenta and bright black are dark, all other colors are light.
+    fn is_dark(color: u32) -> bool {
+        matches!(color, 0 | 1 | 4 | 5 | 8)
+    }
+
+    /// Applies the parameters of a SGR (`ESC [ ... m`) sequence.
+    fn apply_sgr(&mut self, parameters: &[u32]) {
+        if parameters.is_empty() {
+            *self = Style::default();
+        }
+        for parameter in parameters {
+            match *parameter {
+                0 => *self = Style::default(),
+                7 => self.reverse = true,
+                27 => self.reverse = false,
+                30..=37 => self.black_text = Style::is_dark(parameter - 30),
+                39 => self.black_text = true,
+                40..=47 => self.black_background = Style::is_dark(parameter - 40),
+                49 => self.black_background = false,
+                90..=97 => self.black_text = Style::is_dark(parameter - 90 + 8),
+                100..=107 => self.black_background = Style::is_dark(parameter - 100 + 8),
+                _ => {}
+            }
+        }
+    }
+
+    fn cell(&self, c: char) -> Cell {
+        let (mut text, mut background) = (self.black_text, self.black_background);
+        if self.reverse {
+            std::mem::swap(&mut text, &mut background);
+        }
+        if text == background {
+            // Keep text readable if both colors map to the same one.
+            text = !background;
+        }
+        Cell {
+            c,
+            black_text: text,
+            black_background: background,
+        }
+    }
+}
+
+/// State of the parser for escape sequences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Escape {
+    None,
+    Start,
+    Csi(String),
+}
+
+/// Scrolling text console rendered with a bitmap font.
+///
+/// Text is written with `write!` or `write_str()`. ANSI color sequences (`ESC [ ... m`) are mapped to black and white:
+/// dark colors (black, red, blue, magenta) become black, light colors white, and reverse video swaps text and background.
+/// `ESC [ 2J` clears the console and `ESC [ K` the current line, other escape sequences are ignored.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::Console;
+/// use std::fmt::Write;
+///
+/// let mut console = Console::new(10, 2);
+/// writeln!(console, "first").unwrap();
+/// write!(console, "\x1b[41mred\x1b[0m and a long line").unwrap();
+///
+/// // Long lines wrap, old lines scroll out of the view but stay in the scrollback.
+/// assert_eq!(console.get_visible_lines(), vec!["red and a ", "long line"]);
+/// console.scroll_up(2);
+/// assert_eq!(console.get_visible_lines(), vec!["first", "red and a "]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Console {
+    columns: usize,
+    rows: usize,
+    scrollback: usize,
+    lines: VecDeque<Vec<Cell>>,
+    scroll_offset: usize,
+    style: Style,
+    escape: Escape,
+    font: Font,
+}
+
+impl Console {
+    /// Create a console with the given size in characters, using the built-in font.
+    pub fn new(columns: usize, rows: usize) -> Self {
+        let mut lines = VecDeque::new();
+        lines.push_back(Vec::new());
+        Console {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            scrollback: DEFAULT_SCROLLBACK.max(rows),
+            lines,
+            scroll_offset: 0,
+            style: Style::default(),
+            escape: Escape::None,
+            font: Font::builtin(),
+        }
+    }
+
+    /// Create a console that fills the screen with the given font.
+    /// The width of characters is taken from `M`, so proportional fonts may not fit exactly.
+    pub fn for_screen(screen: &Screen, font: Font) -> Self {
+        let advance = font.get_glyph('M').advance.max(1) as u32;
+        let columns = screen.xres() / advance;
+        let rows = screen.yres() / font.get_height().max(1);
+        let mut console = Console::new(columns as usize, rows as usize);
+        console.font = font;
+        console
+    }
+
+    /// Returns the number of characters per line.
+    pub fn get_columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of visible lines.
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Sets the number of lines to keep, at least the visible rows.
+    pub fn set_scrollback(&mut self, scrollback: usize) {
+        self.scrollback = scrollback.max(self.rows);
+        self.trim();
+    }
+
+    /// Removes all text and resets the colors.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.lines.push_back(Vec::new());
+        self.scroll_offset = 0;
+        self.style = Style::default();
+    }
+
+    /// Scrolls the view up into the scrollback by `lines`.
+    pub fn scroll_up(&mut self, lines: usize) {
+        let max_offset = self.lines.len().saturating_sub(self.rows);
+        self.scroll_offset = (self.scroll_offset + lines).min(max_offset);
+    }
+
+    /// Scrolls the view down towards the newest line by `lines`.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+    }
+
+    /// Scrolls the view to the newest line. Writing text scrolls to the newest line as well.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_offset = 0;
+    }
+
+    /// Returns the text of the visible lines without trailing empty lines.
+    pub fn get_visible_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .visible()
+            .map(|line| line.iter().map(|cell| cell.c).collect())
+            .collect();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+        lines
+    }
+
+    fn visible(&self) -> impl Iterator<Item = &Vec<Cell>> {
+        let end = self.lines.len() - self.scroll_offset;
+        let start = end.saturating_sub(self.rows);
+        self.lines.range(start..end)
+    }
+
+    fn trim(&mut self) {
+        while self.lines.len() > self.scrollback {
+            self.lines.pop_front();
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .min(self.lines.len().saturating_sub(self.rows));
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push_back(Vec::new());
+        self.trim();
+    }
+
+    fn current_line(&mut self) -> &mut Vec<Cell> {
+        self.lines
+            .back_mut()
+            .expect("console has at least one line")
+    }
+
+    fn put_char(&mut self, c: char) {
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.current_line().clear(),
+            '\t' => {
+                let spaces = 4 - self.current_line().len() % 4;
+                for _ in 0..spaces {
+                    self.put_char(' ');
+                }
+            }
+            '\x08' => {
+                self.current_line().pop();
+            }
+            c if c.is_control() => {}
+            c => {
+                if self.current_line().len() >= self.columns {
+                    self.new_line();
+                }
+                let cell = self.style.cell(c);
+                self.current_line().push(cell);
+            }
+        }
+    }
+
+    /// Executes a complete CSI sequence with the final character `command`.
+    fn execute_csi(&mut self, parameters: &str, command: char) {
+        let numbers: Vec<u32> = parameters
+            .split(';')
+            .filter(|p| !p.is_empty())
+            .filter_map(|p| p.parse().ok())
+            .collect();
+        match command {
+            'm' => self.style.apply_sgr(&numbers),
+            'J' if numbers.first() == Some(&2) => {
+                let style = self.style;
+                self.clear();
+                self.style = style;
+            }
+            'K' => self.current_line().clear(),
+            _ => {}
+        }
+    }
+
+    /// Draws the visible lines into the image, starting at the top left corner.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::{Console, BLACK, WHITE};
+    /// use image::RgbImage;
+    /// use std::fmt::Write;
+    ///
+    /// let mut console = Console::new(29, 16);
+    /// write!(console, "\x1b[7m ").unwrap();
+    ///
+    /// let mut image = RgbImage::from_pixel(178, 128, WHITE);
+    /// console.draw(&mut image);
+    /// // Reverse video: a black background behind the space.
+    /// assert_eq!(*image.get_pixel(2, 2), BLACK);
+    /// assert_eq!(*image.get_pixel(8, 2), WHITE);
+    /// ```
+    pub fn draw(&self, image: &mut RgbImage) {
+        let height = self.font.get_height();
+        for (row, line) in self.visible().enumerate() {
+            let y = (row as u32 * height) as i32;
+            let mut x = 0;
+            for cell in line {
+                let glyph = self.font.get_glyph(cell.c);
+                if cell.black_background && glyph.advance > 0 {
+                    draw::fill_rectangle(image, (x, y), glyph.advance as u32, height, BLACK);
+                }
+                let color = if cell.black_text { BLACK } else { WHITE };
+                draw::bitmap(
+                    image,
+                    (x + glyph.x_offset, y + glyph.y_offset),
+                    &glyph.bitmap,
+                    color,
+                );
+                x += glyph.advance;
+            }
+        }
+    }
+
+    /// Clears the screen, draws the visible lines and updates the screen.
+    pub fn render(&self, screen: &mut Screen) -> Ev3Result<()> {
+        screen.clear();
+        self.draw(&mut screen.image);
+        screen.update()
+    }
+}
+
+impl fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.scroll_offset = 0;
+        for c in s.chars() {
+            match &mut self.escape {
+                Escape::None if c == '\x1b' => self.escape = Escape::Start,
+                Escape::None => self.put_char(c),
+                Escape::Start if c == '[' => self.escape = Escape::Csi(String::new()),
+                Escape::Start => self.escape = Escape::None,
+                Escape::Csi(parameters) => {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        let parameters = std::mem::take(parameters);
+                        self.escape = Escape::None;
+                        self.execute_csi(&parameters, c);
+                    } else {
+                        parameters.push(c);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `log` backend that writes records to a `Console` on the screen.
+///
+/// Errors are shown in reverse video, warnings are prefixed with `!`.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::screen::{Console, ConsoleLogger, Font};
+/// use ev3dev_lang_rust::Screen;
+/// use log::LevelFilter;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let screen = Screen::new()?;
+/// let console = Console::for_screen(&screen, Font::builtin());
+/// ConsoleLogger::new(console, screen, LevelFilter::Info).install()?;
+///
+/// log::info!("Robot started");
+/// log::error!("Motor stalled");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ConsoleLogger {
+    console: Mutex<Console>,
+    screen: Mutex<Screen>,
+    level: LevelFilter,
+}
+
+impl ConsoleLogger {
+    /// Create a logger for records up to the given level.
+    pub fn new(console: Console, screen: Screen, level: LevelFilter) -> Self {
+        ConsoleLogger {
+            console: Mutex::new(console),
+            screen: Mutex::new(screen),
+            level,
+        }
+    }
+
+    /// Installs the logger as global `log` backend. Fails if another logger is installed already.
+    pub fn install(self) -> Ev3Result<&'static ConsoleLogger> {
+        let level = self.level;
+        let logger: &'static ConsoleLogger = Box::leak(Box::new(self));
+        log::set_logger(logger).map_err(|err| Ev3Error::InternalError {
+            msg: format!("Could not install console logger: {err}"),
+        })?;
+        log::set_max_level(level);
+        Ok(logger)
+    }
+
+    /// Returns the console, e.g. to scroll. Call `refresh()` afterwards to show the changes.
+    pub fn get_console(&self) -> MutexGuard<'_, Console> {
+        self.console.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Draws the console to the screen.
+    pub fn refresh(&self) -> Ev3Result<()> {
+        let console = self.get_console();
+        let mut screen = self.screen.lock().unwrap_or_else(|err| err.into_inner());
+        console.render(&mut screen)
+    }
+}
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        {
+            let mut console = self.get_console();
+            let _ = match record.level() {
+                log::Level::Error => fmt::Write::write_fmt(
+                    &mut *console,
+                    format_args!("\x1b[7m{}\x1b[0m\n", record.args()),
+                ),
+                log::Level::Warn => {
+                    fmt::Write::write_fmt(&mut *console, format_args!("! {}\n", record.args()))
+                }
+                _ => fmt::Write::write_fmt(&mut *console, format_args!("{}\n", record.args())),
+            };
+        }
+        let _ = self.refresh();
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,252 @@
+//! Drawing primitives for `RgbImage`s.
+//!
+//! All functions clip to the image, so shapes may be partially outside of it.
+//! The `Screen` methods `draw_*` and `fill_*` call these functions on `Screen::image`.
+//!
+//! # Example
+//! ```
+//! use ev3dev_lang_rust::screen::{draw, Font, BLACK, WHITE};
+//! use image::RgbImage;
+//!
+//! let mut image = RgbImage::from_pixel(178, 128, WHITE);
+//! draw::rectangle(&mut image, (0, 0), 178, 128, BLACK);
+//! draw::line(&mut image, (0, 0), (177, 127), BLACK);
+//! draw::text(&mut image, (2, 2), "Hello EV3", &Font::builtin(), BLACK);
+//!
+//! assert_eq!(*image.get_pixel(177, 0), BLACK);
+//! // First column of the `H`.
+//! assert_eq!(*image.get_pixel(2, 3), BLACK);
+//! ```
+
+use super::{Bitmap, Font};
+use image::{Rgb, RgbImage};
+
+/// Sets a single pixel. Pixels outside of the image are ignored.
+pub fn pixel(image: &mut RgbImage, x: i32, y: i32, color: Rgb<u8>) {
+    if x >= 0 && y >= 0 && (x as u32) < image.width() && (y as u32) < image.height() {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Draws a horizontal line from `x0` to `x1` inclusive.
+fn horizontal_line(image: &mut RgbImage, x0: i32, x1: i32, y: i32, color: Rgb<u8>) {
+    if y < 0 || y as u32 >= image.height() {
+        return;
+    }
+    let start = x0.min(x1).max(0);
+    let end = x0.max(x1).min(image.width() as i32 - 1);
+    for x in start..=end {
+        image.put_pixel(x as u32, y as u32, color);
+    }
+}
+
+/// Draws a line with the Bresenham algorithm. Both end points are included.
+pub fn line(image: &mut RgbImage, from: (i32, i32), to: (i32, i32), color: Rgb<u8>) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        pixel(image, x, y, color);
+        if (x, y) == to {
+            break;
+        }
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Draws the outline of a rectangle with the given top left corner and size.
+pub fn rectangle(
+    image: &mut RgbImage,
+    top_left: (i32, i32),
+    width: u32,
+    height: u32,
+    color: Rgb<u8>,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (left, top) = top_left;
+    let right = left + width as i32 - 1;
+    let bottom = top + height as i32 - 1;
+
+    horizontal_line(image, left, right, top, color);
+    horizontal_line(image, left, right, bottom, color);
+    for y in top..=bottom {
+        pixel(image, left, y, color);
+        pixel(image, right, y, color);
+    }
+}
+
+/// Fills a rectangle with the given top left corner and size.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::{draw, BLACK, WHITE};
+/// use image::RgbImage;
+///
+/// let mut image = RgbImage::from_pixel(10, 10, WHITE);
+/// draw::fill_rectangle(&mut image, (2, 2), 0, 5, BLACK);
+/// assert!(image.pixels().all(|pixel| *pixel == WHITE));
+///
+/// draw::fill_rectangle(&mut image, (2, 2), 3, u32::MAX, BLACK);
+/// assert_eq!(*image.get_pixel(4, 9), BLACK);
+/// assert_eq!(*image.get_pixel(5, 9), WHITE);
+/// ```
+pub fn fill_rectangle(
+    image: &mut RgbImage,
+    top_left: (i32, i32),
+    width: u32,
+    height: u32,
+    color: Rgb<u8>,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (left, top) = top_left;
+    // Clip in i64, sizes near `u32::MAX` would overflow i32.
+    let right = (left as i64 + width as i64 - 1).min(image.width() as i64) as i32;
+    let start = top.max(0);
+    let end = (top as i64 + height as i64).min(image.height() as i64) as i32;
+    for y in start..end {
+        horizontal_line(image, left, right, y, color);
+    }
+}
+
+/// Calls `plot` with the offsets of one octant of a circle (midpoint algorithm).
+fn circle_octant<F: FnMut(i32, i32)>(radius: u32, mut plot: F) {
+    let mut x = radius as i32;
+    let mut y = 0;
+    let mut error = 1 - x;
+
+    while x >= y {
+        plot(x, y);
+        y += 1;
+        if error < 0 {
+            error += 2 * y + 1;
+        } else {
+            x -= 1;
+            error += 2 * (y - x) + 1;
+        }
+    }
+}
+
+/// Draws the outline of a circle.
+pub fn circle(image: &mut RgbImage, center: (i32, i32), radius: u32, color: Rgb<u8>) {
+    let (cx, cy) = center;
+    circle_octant(radius, |x, y| {
+        for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y)] {
+            pixel(image, cx + dx, cy + dy, color);
+            pixel(image, cx - dx, cy - dy, color);
+        }
+    });
+}
+
+/// Fills a circle.
+pub fn fill_circle(image: &mut RgbImage, center: (i32, i32), radius: u32, color: Rgb<u8>) {
+    let (cx, cy) = center;
+    circle_octant(radius, |x, y| {
+        horizontal_line(image, cx - x, cx + x, cy + y, color);
+        horizontal_line(image, cx - x, cx + x, cy - y, color);
+        horizontal_line(image, cx - y, cx + y, cy + x, color);
+        horizontal_line(image, cx - y, cx + y, cy - x, color);
+    });
+}
+
+/// Fills a polygon with the even-odd rule. The last point is connected to the first one.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::{draw, BLACK, WHITE};
+/// use image::RgbImage;
+///
+/// let mut image = RgbImage::from_pixel(20, 20, WHITE);
+/// draw::fill_polygon(&mut image, &[(2, 2), (17, 2), (2, 17)], BLACK);
+///
+/// assert_eq!(*image.get_pixel(4, 4), BLACK);
+/// assert_eq!(*image.get_pixel(16, 16), WHITE);
+/// ```
+pub fn fill_polygon(image: &mut RgbImage, points: &[(i32, i32)], color: Rgb<u8>) {
+    if points.is_empty() {
+        return;
+    }
+
+    let top = points.iter().map(|p| p.1).min().unwrap_or(0).max(0);
+    let bottom = points
+        .iter()
+        .map(|p| p.1)
+        .max()
+        .unwrap_or(0)
+        .min(image.height() as i32 - 1);
+
+    let mut crossings = Vec::with_capacity(points.len());
+    for y in top..=bottom {
+        // Sample at the pixel center to handle vertices consistently.
+        let scan = y as f32 + 0.5;
+        crossings.clear();
+        for (index, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(index + 1) % points.len()];
+            let (y0f, y1f) = (y0 as f32, y1 as f32);
+            if (y0f <= scan && scan < y1f) || (y1f <= scan && scan < y0f) {
+                let t = (scan - y0f) / (y1f - y0f);
+                crossings.push(x0 as f32 + t * (x1 - x0) as f32);
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+
+        for pair in crossings.chunks_exact(2) {
+            let (start, end) = (pair[0].round() as i32, pair[1].round() as i32 - 1);
+            // Spans narrower than a pixel are covered by the outline.
+            if end >= start {
+                horizontal_line(image, start, end, y, color);
+            }
+        }
+    }
+
+    // Include the outline, so degenerated polygons are still visible.
+    for (index, &point) in points.iter().enumerate() {
+        line(image, point, points[(index + 1) % points.len()], color);
+    }
+}
+
+/// Draws the set pixels of a bitmap. Unset pixels keep the background.
+pub fn bitmap(image: &mut RgbImage, top_left: (i32, i32), bitmap: &Bitmap, color: Rgb<u8>) {
+    for y in 0..bitmap.get_height() {
+        for x in 0..bitmap.get_width() {
+            if bitmap.get_pixel(x, y) {
+                pixel(image, top_left.0 + x as i32, top_left.1 + y as i32, color);
+            }
+        }
+    }
+}
+
+/// Draws text with its top left corner at `top_left`. Line breaks start a new line below.
+pub fn text(image: &mut RgbImage, top_left: (i32, i32), text: &str, font: &Font, color: Rgb<u8>) {
+    let (mut x, mut y) = top_left;
+    for c in text.chars() {
+        if c == '\n' {
+            x = top_left.0;
+            y += font.get_height() as i32;
+            continue;
+        }
+        let glyph = font.get_glyph(c);
+        bitmap(
+            image,
+            (x + glyph.x_offset, y + glyph.y_offset),
+            &glyph.bitmap,
+            color,
+        );
+        x += glyph.advance;
+    }
+}
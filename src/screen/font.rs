@@ -0,0 +1,622 @@
+//! Monochrome bitmaps and bitmap fonts.
+
+use crate::{Ev3Error, Ev3Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Monochrome image with one bit per pixel.
+///
+/// Rows are stored from top to bottom, each padded to whole bytes with the leftmost pixel in the most significant bit.
+/// This is the layout of PSF and BDF glyphs and of the EV3 framebuffer.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::Bitmap;
+///
+/// // A 3x2 arrow: the rows `010` and `111`.
+/// let arrow = Bitmap::from_bytes(3, 2, vec![0b0100_0000, 0b1110_0000]).unwrap();
+/// assert!(arrow.get_pixel(1, 0));
+/// assert!(!arrow.get_pixel(0, 0));
+/// assert!(arrow.get_pixel(2, 1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Create a bitmap with all pixels unset.
+    pub fn new(width: u32, height: u32) -> Self {
+        Bitmap {
+            width,
+            height,
+            data: vec![0; (Self::stride_of(width) * height) as usize],
+        }
+    }
+
+    /// Create a bitmap from packed rows. Fails if `data` has not exactly `ceil(width / 8) * height` bytes.
+    pub fn from_bytes(width: u32, height: u32, data: Vec<u8>) -> Ev3Result<Self> {
+        let expected = (Self::stride_of(width) * height) as usize;
+        if data.len() != expected {
+            return Err(Ev3Error::InternalError {
+                msg: format!(
+                    "Bitmap of {width}x{height} pixels needs {expected} bytes, got {}",
+                    data.len()
+                ),
+            });
+        }
+        Ok(Bitmap {
+            width,
+            height,
+            data,
+        })
+    }
+
+    fn stride_of(width: u32) -> u32 {
+        width.div_ceil(8)
+    }
+
+    /// Returns the width in pixels.
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height in pixels.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the packed rows.
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Checks if the pixel is set. Pixels outside of the bitmap are unset.
+    pub fn get_pixel(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let byte = (y * Self::stride_of(self.width) + x / 8) as usize;
+        self.data[byte] & (0x80 >> (x % 8)) != 0
+    }
+
+    /// Sets or unsets a pixel. Pixels outside of the bitmap are ignored.
+    pub fn set_pixel(&mut self, x: u32, y: u32, value: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let byte = (y * Self::stride_of(self.width) + x / 8) as usize;
+        if value {
+            self.data[byte] |= 0x80 >> (x % 8);
+        } else {
+            self.data[byte] &= !(0x80 >> (x % 8));
+        }
+    }
+}
+
+/// A single character of a `Font`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Pixels of the character.
+    pub bitmap: Bitmap,
+    /// Horizontal distance from the pen position to the left edge of the bitmap.
+    pub x_offset: i32,
+    /// Vertical distance from the top of the line to the top edge of the bitmap.
+    pub y_offset: i32,
+    /// Horizontal distance to the pen position of the next character.
+    pub advance: i32,
+}
+
+/// Bitmap font for the monochrome display.
+///
+/// `Font::builtin()` is a 5x7 pixel ASCII font in a 6x8 cell, so the 178x128 EV3 display fits 29 characters in 16 lines.
+/// Console fonts in PSF format (version 1 and 2) and X11 fonts in BDF format can be loaded as well.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::Font;
+///
+/// let font = Font::builtin();
+/// assert_eq!(font.get_height(), 8);
+/// assert_eq!(font.text_width("Hello"), 30);
+/// assert!(font.get_glyph('A').bitmap.get_pixel(2, 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    height: u32,
+    glyphs: HashMap<char, Glyph>,
+    replacement: Glyph,
+}
+
+impl Font {
+    /// Create a font from its glyphs. Characters without a glyph are drawn as `replacement`.
+    pub fn new(height: u32, glyphs: HashMap<char, Glyph>, replacement: Glyph) -> Self {
+        Font {
+            height,
+            glyphs,
+            replacement,
+        }
+    }
+
+    /// The bundled 5x7 pixel ASCII font with one pixel of spacing to the right and below each character.
+    pub fn builtin() -> Self {
+        let glyphs: HashMap<char, Glyph> = FONT_5X7
+            .iter()
+            .enumerate()
+            .map(|(index, columns)| {
+                let mut bitmap = Bitmap::new(5, 7);
+                for (x, column) in columns.iter().enumerate() {
+                    for y in 0..7 {
+                        bitmap.set_pixel(x as u32, y, column & (1 << y) != 0);
+                    }
+                }
+                let glyph = Glyph {
+                    bitmap,
+                    x_offset: 0,
+                    y_offset: 0,
+                    advance: 6,
+                };
+                (char::from(b' ' + index as u8), glyph)
+            })
+            .collect();
+
+        let replacement = glyphs[&'?'].clone();
+        Font::new(8, glyphs, replacement)
+    }
+
+    /// Parses a PC screen font (PSF version 1 or 2).
+    /// Glyphs are assigned by the unicode table of the font or, if it has none, by their index.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::Font;
+    ///
+    /// // PSF2 header with glyphs that are too wide to fit in memory.
+    /// let mut header = vec![0x72, 0xb5, 0x4a, 0x86, 0, 0, 0, 0];
+    /// for value in [32u32, 0, 1, 2, 16, u32::MAX] {
+    ///     header.extend(value.to_le_bytes());
+    /// }
+    /// assert!(Font::from_psf(&header).is_err());
+    ///
+    /// // Empty glyphs would need no glyph data for any number of glyphs.
+    /// let mut header = vec![0x72, 0xb5, 0x4a, 0x86, 0, 0, 0, 0];
+    /// for value in [32u32, 0, u32::MAX, 0, 0, 8] {
+    ///     header.extend(value.to_le_bytes());
+    /// }
+    /// assert!(Font::from_psf(&header).is_err());
+    /// ```
+    pub fn from_psf(bytes: &[u8]) -> Ev3Result<Self> {
+        let invalid = |msg: &str| Ev3Error::InternalError {
+            msg: format!("Invalid PSF font: {msg}"),
+        };
+        let read_u32 = |offset: usize| -> Ev3Result<u32> {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or_else(|| invalid("truncated header"))
+        };
+
+        let (width, height, count, glyph_size, header_size, has_table, unicode_psf2) =
+            if bytes.starts_with(&[0x36, 0x04]) && bytes.len() >= 4 {
+                let mode = bytes[2];
+                let height = bytes[3] as u32;
+                let count = if mode & 0x01 != 0 { 512 } else { 256 };
+                (8, height, count, height, 4, mode & 0x06 != 0, false)
+            } else if bytes.starts_with(&[0x72, 0xb5, 0x4a, 0x86]) {
+                let header_size = read_u32(8)?;
+                let flags = read_u32(12)?;
+                let count = read_u32(16)?;
+                let glyph_size = read_u32(20)?;
+                let height = read_u32(24)?;
+                let width = read_u32(28)?;
+                (
+                    width,
+                    height,
+                    count,
+                    glyph_size,
+                    header_size,
+                    flags & 0x01 != 0,
+                    true,
+                )
+            } else {
+                return Err(invalid("unknown magic number"));
+            };
+
+        if width.div_ceil(8).checked_mul(height) != Some(glyph_size) {
+            return Err(invalid("glyph size does not match dimensions"));
+        }
+        if glyph_size == 0 {
+            return Err(invalid("empty glyphs"));
+        }
+        // One glyph per unicode code point at most.
+        if count > 0x110000 {
+            return Err(invalid("too many glyphs"));
+        }
+
+        let glyphs_start = header_size as usize;
+        let glyphs_end = (count as usize)
+            .checked_mul(glyph_size as usize)
+            .and_then(|size| size.checked_add(glyphs_start))
+            .ok_or_else(|| invalid("glyph data too large"))?;
+        if bytes.len() < glyphs_end {
+            return Err(invalid("truncated glyph data"));
+        }
+
+        let bitmaps = (0..count as usize)
+            .map(|index| {
+                let start = glyphs_start + index * glyph_size as usize;
+                let data = bytes[start..start + glyph_size as usize].to_vec();
+                Bitmap::from_bytes(width, height, data)
+            })
+            .collect::<Ev3Result<Vec<_>>>()?;
+
+        let mut chars: Vec<Vec<char>> = vec![Vec::new(); count as usize];
+        if has_table {
+            let mut table = &bytes[glyphs_end..];
+            for entry in chars.iter_mut() {
+                if unicode_psf2 {
+                    // UTF-8 strings terminated by 0xFF, combining sequences start with 0xFE.
+                    let end = table.iter().position(|b| *b == 0xff).unwrap_or(table.len());
+                    let singles = table[..end].split(|b| *b == 0xfe).next().unwrap_or(&[]);
+                    entry.extend(String::from_utf8_lossy(singles).chars());
+                    table = table.get(end + 1..).unwrap_or(&[]);
+                } else {
+                    // UCS-2 values terminated by 0xFFFF, combining sequences start with 0xFFFE.
+                    let mut in_sequence = false;
+                    while table.len() >= 2 {
+                        let value = u16::from_le_bytes([table[0], table[1]]);
+                        table = &table[2..];
+                        match value {
+                            0xffff => break,
+                            0xfffe => in_sequence = true,
+                            _ if !in_sequence => entry.extend(char::from_u32(value as u32)),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        } else {
+            for (index, entry) in chars.iter_mut().enumerate() {
+                entry.extend(char::from_u32(index as u32));
+            }
+        }
+
+        let mut glyphs = HashMap::new();
+        for (bitmap, chars) in bitmaps.into_iter().zip(chars) {
+            for c in chars {
+                glyphs.entry(c).or_insert_with(|| Glyph {
+                    bitmap: bitmap.clone(),
+                    x_offset: 0,
+                    y_offset: 0,
+                    advance: width as i32,
+                });
+            }
+        }
+
+        let replacement = glyphs.get(&'?').cloned().unwrap_or_else(|| Glyph {
+            bitmap: Bitmap::new(width, height),
+            x_offset: 0,
+            y_offset: 0,
+            advance: width as i32,
+        });
+        Ok(Font::new(height, glyphs, replacement))
+    }
+
+    /// Parses a font in the Glyph Bitmap Distribution Format (BDF).
+    /// Glyphs without unicode encoding (`ENCODING -1`) are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::Font;
+    ///
+    /// let bdf = "\
+    /// STARTFONT 2.1
+    /// FONTBOUNDINGBOX 3 4 0 -1
+    /// STARTPROPERTIES 2
+    /// FONT_ASCENT 3
+    /// FONT_DESCENT 1
+    /// ENDPROPERTIES
+    /// STARTCHAR period
+    /// ENCODING 46
+    /// DWIDTH 2 0
+    /// BBX 1 1 0 0
+    /// BITMAP
+    /// 80
+    /// ENDCHAR
+    /// ENDFONT
+    /// ";
+    /// let font = Font::from_bdf(bdf).unwrap();
+    ///
+    /// assert_eq!(font.get_height(), 4);
+    /// let period = font.get_glyph('.');
+    /// // The period ends at the baseline, 3 pixels below the top of the line.
+    /// assert_eq!((period.y_offset, period.advance), (2, 2));
+    /// assert!(period.bitmap.get_pixel(0, 0));
+    ///
+    /// // Bitmap rows have to be hexadecimal.
+    /// assert!(Font::from_bdf(&bdf.replace("\n80\n", "\n8é\n")).is_err());
+    /// // Glyphs have to fit into the font bounding box and the file.
+    /// assert!(Font::from_bdf(&bdf.replace("BBX 1 1", "BBX 2147483647 1")).is_err());
+    /// assert!(Font::from_bdf(&bdf.replace("BBX 1 1", "BBX 1 2147483647")).is_err());
+    /// ```
+    pub fn from_bdf(text: &str) -> Ev3Result<Self> {
+        let mut ascent: Option<i32> = None;
+        let mut descent: Option<i32> = None;
+        let mut bounding_box = (0, 0, 0, 0);
+
+        let mut glyphs = HashMap::new();
+        // A glyph cannot have more rows than the file has lines.
+        let max_rows = text.lines().count();
+        let mut lines = text.lines().enumerate();
+
+        while let Some((number, line)) = lines.next() {
+            let invalid = || Ev3Error::InternalError {
+                msg: format!("Invalid BDF font in line {}: {line}", number + 1),
+            };
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("FONTBOUNDINGBOX") => bounding_box = parse_bbx(parts).ok_or_else(invalid)?,
+                Some("FONT_ASCENT") => {
+                    ascent = Some(
+                        parts
+                            .next()
+                            .and_then(|v| v.parse().ok())
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                Some("FONT_DESCENT") => {
+                    descent = Some(
+                        parts
+                            .next()
+                            .and_then(|v| v.parse().ok())
+                            .ok_or_else(invalid)?,
+                    )
+                }
+                Some("STARTCHAR") => {
+                    let ascent = ascent.unwrap_or(bounding_box.1 + bounding_box.3);
+                    let (encoding, glyph) =
+                        parse_bdf_char(&mut lines, ascent, bounding_box, max_rows)?;
+                    if let Some(c) = encoding {
+                        glyphs.insert(c, glyph);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let height = match (ascent, descent) {
+            (Some(ascent), Some(descent)) => ascent + descent,
+            _ => bounding_box.1,
+        };
+        let replacement = glyphs.get(&'?').cloned().unwrap_or_else(|| Glyph {
+            bitmap: Bitmap::new(0, 0),
+            x_offset: 0,
+            y_offset: 0,
+            advance: bounding_box.0,
+        });
+        Ok(Font::new(height.max(0) as u32, glyphs, replacement))
+    }
+
+    /// Reads a PSF font file, e.g. from `/usr/share/consolefonts`.
+    /// Compressed fonts (`.psf.gz`) have to be decompressed first.
+    pub fn load_psf<P: AsRef<Path>>(path: P) -> Ev3Result<Self> {
+        Font::from_psf(&fs::read(path)?)
+    }
+
+    /// Reads a BDF font file.
+    pub fn load_bdf<P: AsRef<Path>>(path: P) -> Ev3Result<Self> {
+        Font::from_bdf(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the line height in pixels.
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the glyph of a character or the replacement glyph if the font does not contain it.
+    pub fn get_glyph(&self, c: char) -> &Glyph {
+        self.glyphs.get(&c).unwrap_or(&self.replacement)
+    }
+
+    /// Checks if the font contains a glyph for the character.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    /// Returns the width of the widest line of the text in pixels.
+    pub fn text_width(&self, text: &str) -> u32 {
+        text.lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| self.get_glyph(c).advance)
+                    .sum::<i32>()
+                    .max(0) as u32
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Parses the four numbers of `BBX` and `FONTBOUNDINGBOX`: width, height, x offset, y offset.
+fn parse_bbx<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<(i32, i32, i32, i32)> {
+    let mut next = || parts.next().and_then(|v| v.parse().ok());
+    Some((next()?, next()?, next()?, next()?))
+}
+
+/// Parses the lines after `STARTCHAR` up to `ENDCHAR`.
+/// The `BBX` of the glyph has to fit into the width of the `FONTBOUNDINGBOX` and `max_rows`.
+fn parse_bdf_char<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    ascent: i32,
+    bounding_box: (i32, i32, i32, i32),
+    max_rows: usize,
+) -> Ev3Result<(Option<char>, Glyph)> {
+    let mut encoding = None;
+    let mut advance = bounding_box.0;
+    let mut bbx = bounding_box;
+
+    while let Some((number, line)) = lines.next() {
+        let invalid = || Ev3Error::InternalError {
+            msg: format!("Invalid BDF font in line {}: {line}", number + 1),
+        };
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("ENCODING") => {
+                let code: i64 = parts
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(invalid)?;
+                encoding = u32::try_from(code).ok().and_then(char::from_u32);
+            }
+            Some("DWIDTH") => {
+                advance = parts
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(invalid)?;
+            }
+            Some("BBX") => bbx = parse_bbx(parts).ok_or_else(invalid)?,
+            Some("BITMAP") => {
+                let (width, height) = (bbx.0.max(0) as u32, bbx.1.max(0) as u32);
+                if width > bounding_box.0.max(0) as u32 || height as usize > max_rows {
+                    return Err(invalid());
+                }
+                let stride = width.div_ceil(8) as usize;
+                let size = stride.checked_mul(height as usize).ok_or_else(invalid)?;
+                let mut data = Vec::with_capacity(size);
+
+                for _ in 0..height {
+                    let (number, row) = lines.next().ok_or_else(invalid)?;
+                    let row = row.trim();
+                    if !row.is_ascii() {
+                        return Err(Ev3Error::InternalError {
+                            msg: format!("Invalid BDF font in line {}: {row}", number + 1),
+                        });
+                    }
+                    let mut bytes = (0..row.len() / 2)
+                        .map(|i| u8::from_str_radix(&row[2 * i..2 * i + 2], 16))
+                        .collect::<Result<Vec<u8>, _>>()
+                        .map_err(|_| Ev3Error::InternalError {
+                            msg: format!("Invalid BDF font in line {}: {row}", number + 1),
+                        })?;
+                    bytes.resize(stride, 0);
+                    data.extend(bytes);
+                }
+
+                let glyph = Glyph {
+                    bitmap: Bitmap::from_bytes(width, height, data)?,
+                    x_offset: bbx.2,
+                    y_offset: ascent - (bbx.1 + bbx.3),
+                    advance,
+                };
+                return Ok((encoding, glyph));
+            }
+            _ => {}
+        }
+    }
+
+    Err(Ev3Error::InternalError {
+        msg: "Invalid BDF font: missing BITMAP".to_owned(),
+    })
+}
+
+/// Columns of the printable ASCII characters, least significant bit at the top.
+const FONT_5X7: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // '#'
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x55, 0x22, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '''
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // ')'
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // '*'
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // '0'
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // '@'
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // 'A'
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // 'D'
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // 'F'
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // 'G'
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // 'H'
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // 'J'
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // 'M'
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // 'N'
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // 'O'
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // 'Q'
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // 'T'
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // 'U'
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // 'V'
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x07, 0x08, 0x70, 0x08, 0x07], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\'
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // 'f'
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // 'g'
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // 'j'
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // 'k'
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // 'l'
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // 'q'
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // 't'
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // 'u'
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // 'v'
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // 'y'
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x08, 0x04, 0x08, 0x10, 0x08], // '~'
+];
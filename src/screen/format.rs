@@ -0,0 +1,306 @@
+//! Pixel layouts of Linux framebuffers.
+
+use super::monochrome::brightness;
+use super::{to_monochrome, Dithering};
+use crate::{Ev3Error, Ev3Result};
+use framebuffer::{FixScreeninfo, VarScreeninfo};
+use image::{Rgb, RgbImage};
+use std::collections::HashMap;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+
+/// `FBIOGETCMAP` ioctl to read the color map of palette framebuffers.
+const FBIOGETCMAP: u32 = 0x4604;
+
+/// `visual` values of `fb_fix_screeninfo`, see `include/uapi/linux/fb.h`.
+const FB_VISUAL_MONO01: u32 = 0;
+const FB_VISUAL_MONO10: u32 = 1;
+const FB_VISUAL_TRUECOLOR: u32 = 2;
+const FB_VISUAL_PSEUDOCOLOR: u32 = 3;
+const FB_VISUAL_DIRECTCOLOR: u32 = 4;
+const FB_VISUAL_STATIC_PSEUDOCOLOR: u32 = 5;
+
+#[repr(C)]
+struct FbCmap {
+    start: u32,
+    len: u32,
+    red: *mut u16,
+    green: *mut u16,
+    blue: *mut u16,
+    transp: *mut u16,
+}
+
+/// Position of a color component within a pixel value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Channel {
+    /// Bit offset of the least significant bit.
+    pub offset: u32,
+    /// Number of bits.
+    pub length: u32,
+}
+
+impl Channel {
+    /// Scales an 8 bit component to the channel length and shifts it to its position.
+    fn encode(&self, value: u8) -> u32 {
+        if self.length == 0 {
+            return 0;
+        }
+        let scaled = if self.length >= 8 {
+            (value as u32) << (self.length - 8)
+        } else {
+            (value as u32) >> (8 - self.length)
+        };
+        scaled << self.offset
+    }
+}
+
+/// Pixel layout of a framebuffer, derived from its screen info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One bit per pixel.
+    Monochrome {
+        /// `true` if set bits are white, `false` if set bits are black.
+        set_is_white: bool,
+    },
+    /// Components at the given bit positions of each pixel, e.g. RGB565 or XRGB8888.
+    TrueColor {
+        /// Number of bits per pixel.
+        bits_per_pixel: u32,
+        /// Red component.
+        red: Channel,
+        /// Green component.
+        green: Channel,
+        /// Blue component.
+        blue: Channel,
+        /// Alpha component, always set to opaque.
+        transp: Channel,
+        /// If `true`, the brightness is stored in every channel.
+        grayscale: bool,
+    },
+    /// Indices into a color map with up to 8 bits per pixel.
+    Palette {
+        /// Number of bits per pixel.
+        bits_per_pixel: u32,
+        /// Colors of the color map by index.
+        colors: Vec<Rgb<u8>>,
+    },
+}
+
+impl PixelFormat {
+    /// Derives the pixel layout. Fails for layouts that cannot be encoded.
+    pub fn from_screen_info(
+        device: &File,
+        var: &VarScreeninfo,
+        fix: &FixScreeninfo,
+    ) -> Ev3Result<PixelFormat> {
+        let bits_per_pixel = var.bits_per_pixel;
+        let unsupported = || Ev3Error::InternalError {
+            msg: format!(
+                "Unsupported framebuffer format: {bits_per_pixel} bpp, visual {}, grayscale {}",
+                fix.visual, var.grayscale
+            ),
+        };
+
+        if !matches!(bits_per_pixel, 1 | 2 | 4 | 8 | 16 | 24 | 32) || var.grayscale > 1 {
+            // `grayscale` values above 1 are FOURCC codes of non standard formats.
+            return Err(unsupported());
+        }
+
+        let channel = |field: &framebuffer::Bitfield| Channel {
+            offset: field.offset,
+            length: field.length,
+        };
+
+        match fix.visual {
+            FB_VISUAL_MONO01 | FB_VISUAL_MONO10 if bits_per_pixel == 1 => {
+                Ok(PixelFormat::Monochrome {
+                    set_is_white: fix.visual == FB_VISUAL_MONO10,
+                })
+            }
+            FB_VISUAL_TRUECOLOR | FB_VISUAL_DIRECTCOLOR => {
+                let (red, green, blue) =
+                    (channel(&var.red), channel(&var.green), channel(&var.blue));
+                let transp = channel(&var.transp);
+                let fits = [red, green, blue, transp]
+                    .iter()
+                    .all(|c| c.length <= 32 && c.offset + c.length <= bits_per_pixel);
+                if !fits || red.length + green.length + blue.length == 0 {
+                    return Err(unsupported());
+                }
+                if bits_per_pixel == 1 {
+                    // Drivers that report a monochrome display as true color, set bits are white.
+                    return Ok(PixelFormat::Monochrome { set_is_white: true });
+                }
+                Ok(PixelFormat::TrueColor {
+                    bits_per_pixel,
+                    red,
+                    green,
+                    blue,
+                    transp,
+                    grayscale: var.grayscale == 1,
+                })
+            }
+            FB_VISUAL_PSEUDOCOLOR | FB_VISUAL_STATIC_PSEUDOCOLOR if bits_per_pixel <= 8 => {
+                Ok(PixelFormat::Palette {
+                    bits_per_pixel,
+                    colors: read_color_map(device, 1 << bits_per_pixel)?,
+                })
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    /// Returns the number of bits per pixel.
+    pub fn get_bits_per_pixel(&self) -> u32 {
+        match self {
+            PixelFormat::Monochrome { .. } => 1,
+            PixelFormat::TrueColor { bits_per_pixel, .. }
+            | PixelFormat::Palette { bits_per_pixel, .. } => *bits_per_pixel,
+        }
+    }
+
+    /// Encodes the image in rows of `line_length` bytes.
+    ///
+    /// Pixels with less than 8 bits are packed with the leftmost pixel in the most significant bits,
+    /// larger pixels are stored in little endian byte order.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::{Channel, Dithering, PixelFormat};
+    /// use image::{Rgb, RgbImage};
+    ///
+    /// let mut image = RgbImage::from_pixel(2, 1, Rgb([255, 255, 255]));
+    /// image.put_pixel(1, 0, Rgb([255, 0, 0]));
+    ///
+    /// let rgb565 = PixelFormat::TrueColor {
+    ///     bits_per_pixel: 16,
+    ///     red: Channel { offset: 11, length: 5 },
+    ///     green: Channel { offset: 5, length: 6 },
+    ///     blue: Channel { offset: 0, length: 5 },
+    ///     transp: Channel { offset: 0, length: 0 },
+    ///     grayscale: false,
+    /// };
+    /// assert_eq!(rgb565.encode(&image, Dithering::default(), 4), vec![0xff, 0xff, 0x00, 0xf8]);
+    ///
+    /// // The red pixel is dark enough to become black with a brightness threshold.
+    /// let mono = PixelFormat::Monochrome { set_is_white: false };
+    /// assert_eq!(mono.encode(&image, Dithering::Threshold(128), 1), vec![0b0100_0000]);
+    /// ```
+    pub fn encode(&self, image: &RgbImage, dithering: Dithering, line_length: usize) -> Vec<u8> {
+        let mut frame = vec![0u8; line_length * image.height() as usize];
+        let bits_per_pixel = self.get_bits_per_pixel() as usize;
+        let width = (image.width() as usize).min(line_length * 8 / bits_per_pixel);
+
+        let mut write = |x: usize, y: usize, value: u32| {
+            let row = &mut frame[y * line_length..(y + 1) * line_length];
+            if bits_per_pixel >= 8 {
+                let bytes = bits_per_pixel / 8;
+                row[x * bytes..(x + 1) * bytes].copy_from_slice(&value.to_le_bytes()[..bytes]);
+            } else {
+                let bit = x * bits_per_pixel;
+                let shift = 8 - bits_per_pixel - bit % 8;
+                row[bit / 8] |= (value as u8) << shift;
+            }
+        };
+
+        match self {
+            PixelFormat::Monochrome { set_is_white } => {
+                let bitmap = to_monochrome(image, dithering);
+                for y in 0..image.height() {
+                    for x in 0..width {
+                        let black = bitmap.get_pixel(x as u32, y);
+                        write(x, y as usize, (black != *set_is_white) as u32);
+                    }
+                }
+            }
+            PixelFormat::TrueColor {
+                red,
+                green,
+                blue,
+                transp,
+                grayscale,
+                ..
+            } => {
+                for (x, y, pixel) in image.enumerate_pixels() {
+                    if x as usize >= width {
+                        continue;
+                    }
+                    let [r, g, b] = if *grayscale {
+                        [brightness(pixel); 3]
+                    } else {
+                        pixel.0
+                    };
+                    let value =
+                        red.encode(r) | green.encode(g) | blue.encode(b) | transp.encode(255);
+                    write(x as usize, y as usize, value);
+                }
+            }
+            PixelFormat::Palette { colors, .. } => {
+                let mut cache: HashMap<Rgb<u8>, u32> = HashMap::new();
+                for (x, y, pixel) in image.enumerate_pixels() {
+                    if x as usize >= width {
+                        continue;
+                    }
+                    let index = *cache
+                        .entry(*pixel)
+                        .or_insert_with(|| nearest_color(colors, pixel));
+                    write(x as usize, y as usize, index);
+                }
+            }
+        }
+
+        frame
+    }
+}
+
+/// Index of the palette color with the smallest squared distance.
+fn nearest_color(colors: &[Rgb<u8>], pixel: &Rgb<u8>) -> u32 {
+    colors
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| {
+            color
+                .0
+                .iter()
+                .zip(pixel.0.iter())
+                .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+                .sum::<i32>()
+        })
+        .map(|(index, _)| index as u32)
+        .unwrap_or(0)
+}
+
+/// Reads `len` entries of the color map with 16 bit components.
+fn read_color_map(device: &File, len: usize) -> Ev3Result<Vec<Rgb<u8>>> {
+    let mut red = vec![0u16; len];
+    let mut green = vec![0u16; len];
+    let mut blue = vec![0u16; len];
+
+    let mut cmap = FbCmap {
+        start: 0,
+        len: len as u32,
+        red: red.as_mut_ptr(),
+        green: green.as_mut_ptr(),
+        blue: blue.as_mut_ptr(),
+        transp: std::ptr::null_mut(),
+    };
+    let result = unsafe { libc::ioctl(device.as_raw_fd(), FBIOGETCMAP as _, &mut cmap) };
+    if result < 0 {
+        return Err(Ev3Error::InternalError {
+            msg: format!(
+                "Could not read framebuffer color map: {}",
+                std::io::Error::last_os_error()
+            ),
+        });
+    }
+
+    Ok((0..len)
+        .map(|i| {
+            Rgb([
+                (red[i] >> 8) as u8,
+                (green[i] >> 8) as u8,
+                (blue[i] >> 8) as u8,
+            ])
+        })
+        .collect())
+}
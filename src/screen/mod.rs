@@ -0,0 +1,312 @@
+//! Access to the display with drawing primitives, bitmap fonts and dithering for monochrome displays.
+//!
+//! Besides the framebuffer, a `Screen` can render headless to PNG files or memory,
+//! so user interfaces can be developed and tested on a PC.
+
+use framebuffer::Framebuffer;
+
+use image::{Rgb, RgbImage};
+
+use crate::{Ev3Error, Ev3Result};
+
+pub mod draw;
+pub mod ui;
+
+mod console;
+pub use self::console::{Console, ConsoleLogger};
+
+mod font;
+pub use self::font::{Bitmap, Font, Glyph};
+
+mod format;
+pub use self::format::{Channel, PixelFormat};
+
+mod monochrome;
+pub use self::monochrome::{to_monochrome, Dithering};
+
+mod backend;
+use self::backend::{encode_png, FramebufferOutput, HeadlessRenderer};
+pub use self::backend::{HeadlessOutput, ScreenBackend, EV3_XRES, EV3_YRES, SCREEN_BACKEND_ENV};
+
+/// Black, the foreground color of the EV3 display.
+pub const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+/// White, the background color of the EV3 display.
+pub const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// Represents the device screen.
+///
+/// Shapes and text can be drawn with the `draw_*` and `fill_*` methods,
+/// advanced drawing operations can be performed with the `imageproc` crate.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::screen::{Font, BLACK};
+/// use ev3dev_lang_rust::Screen;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut screen = Screen::new()?;
+/// let font = Font::builtin();
+///
+/// screen.clear();
+/// screen.draw_text((2, 2), "Battery", &font, BLACK);
+/// screen.draw_rectangle((2, 12), 100, 10, BLACK);
+/// screen.fill_rectangle((4, 14), 70, 6, BLACK);
+/// screen.update()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Screen {
+    /// Convenience layer to access the framebuffer
+    /// For drawing operations the `imageproc` crate can be used.
+    pub image: RgbImage,
+    backend: Backend,
+    dithering: Dithering,
+}
+
+#[derive(Debug)]
+enum Backend {
+    Framebuffer(Box<FramebufferOutput>),
+    Headless(HeadlessRenderer),
+}
+
+impl Screen {
+    /// Create a reference to the device screen `/dev/fb0`.
+    /// Fails if the pixel format of the framebuffer is not supported.
+    pub fn new() -> Ev3Result<Self> {
+        Screen::open(ScreenBackend::default())
+    }
+
+    /// Create a headless screen with the resolution of the EV3 display.
+    pub fn headless(output: HeadlessOutput) -> Ev3Result<Self> {
+        Screen::open(ScreenBackend::Headless(output))
+    }
+
+    /// Create a screen with the given backend, e.g. `ScreenBackend::from_env()?` to select it at runtime.
+    pub fn open(backend: ScreenBackend) -> Ev3Result<Self> {
+        let (backend, width, height) = match backend {
+            ScreenBackend::Framebuffer(path) => {
+                let output = Box::new(FramebufferOutput::open(path)?);
+                let info = &output.buffer.var_screen_info;
+                let width = output.buffer.fix_screen_info.line_length * 8 / info.bits_per_pixel;
+                let height = info.yres;
+                (Backend::Framebuffer(output), width, height)
+            }
+            ScreenBackend::Headless(output) => (
+                Backend::Headless(HeadlessRenderer::new(output)?),
+                EV3_XRES,
+                EV3_YRES,
+            ),
+        };
+
+        Ok(Self {
+            image: RgbImage::from_pixel(width, height, WHITE),
+            backend,
+            dithering: Dithering::default(),
+        })
+    }
+
+    /// Direct reference to the framebuffer. `None` for headless screens.
+    pub fn get_framebuffer(&self) -> Option<&Framebuffer> {
+        match &self.backend {
+            Backend::Framebuffer(output) => Some(&output.buffer),
+            Backend::Headless(_) => None,
+        }
+    }
+
+    /// Direct mutable reference to the framebuffer. `None` for headless screens.
+    /// Call `invalidate()` after writing to it.
+    pub fn get_framebuffer_mut(&mut self) -> Option<&mut Framebuffer> {
+        match &mut self.backend {
+            Backend::Framebuffer(output) => Some(&mut output.buffer),
+            Backend::Headless(_) => None,
+        }
+    }
+
+    /// Checks if the screen renders without display hardware.
+    pub fn is_headless(&self) -> bool {
+        matches!(self.backend, Backend::Headless(_))
+    }
+
+    /// Returns the last frame of a headless screen as black and white image,
+    /// or `None` for framebuffer screens and before the first `update()`.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::screen::{HeadlessOutput, BLACK, WHITE};
+    /// use ev3dev_lang_rust::Screen;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut screen = Screen::headless(HeadlessOutput::Memory)?;
+    /// assert_eq!(screen.shape(), (178, 128));
+    ///
+    /// screen.fill_rectangle((0, 0), 10, 10, image::Rgb([10, 10, 10]));
+    /// screen.update()?;
+    ///
+    /// let frame = screen.get_rendered_frame().unwrap();
+    /// assert_eq!(*frame.get_pixel(5, 5), BLACK);
+    /// assert_eq!(*frame.get_pixel(50, 50), WHITE);
+    /// assert!(screen.get_rendered_png()?.starts_with(b"\x89PNG"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_rendered_frame(&self) -> Option<&RgbImage> {
+        match &self.backend {
+            Backend::Framebuffer(_) => None,
+            Backend::Headless(renderer) => renderer.frame.as_ref(),
+        }
+    }
+
+    /// Returns the last frame of a headless screen encoded as PNG.
+    /// Fails for framebuffer screens and before the first `update()`.
+    pub fn get_rendered_png(&self) -> Ev3Result<Vec<u8>> {
+        match self.get_rendered_frame() {
+            Some(frame) => encode_png(frame),
+            None => Err(Ev3Error::InternalError {
+                msg: "No frame has been rendered by a headless screen".to_owned(),
+            }),
+        }
+    }
+
+    /// Horizontal screen resolution
+    pub fn xres(&self) -> u32 {
+        match &self.backend {
+            Backend::Framebuffer(output) => output.buffer.var_screen_info.xres,
+            Backend::Headless(_) => EV3_XRES,
+        }
+    }
+
+    /// Vertical screen resolution
+    pub fn yres(&self) -> u32 {
+        match &self.backend {
+            Backend::Framebuffer(output) => output.buffer.var_screen_info.yres,
+            Backend::Headless(_) => EV3_YRES,
+        }
+    }
+
+    /// Dimensions of the screen.
+    pub fn shape(&self) -> (u32, u32) {
+        (self.xres(), self.yres())
+    }
+
+    /// Returns the pixel layout of the framebuffer. Headless screens emulate a monochrome display.
+    pub fn get_pixel_format(&self) -> &PixelFormat {
+        match &self.backend {
+            Backend::Framebuffer(output) => &output.format,
+            Backend::Headless(renderer) => &renderer.format,
+        }
+    }
+
+    /// Clears the screen
+    pub fn clear(&mut self) {
+        for (_, _, pixel) in self.image.enumerate_pixels_mut() {
+            *pixel = WHITE;
+        }
+    }
+
+    /// Draws a line. Both end points are included.
+    pub fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), color: Rgb<u8>) {
+        draw::line(&mut self.image, from, to, color);
+    }
+
+    /// Draws the outline of a rectangle with the given top left corner and size.
+    pub fn draw_rectangle(
+        &mut self,
+        top_left: (i32, i32),
+        width: u32,
+        height: u32,
+        color: Rgb<u8>,
+    ) {
+        draw::rectangle(&mut self.image, top_left, width, height, color);
+    }
+
+    /// Fills a rectangle with the given top left corner and size.
+    pub fn fill_rectangle(
+        &mut self,
+        top_left: (i32, i32),
+        width: u32,
+        height: u32,
+        color: Rgb<u8>,
+    ) {
+        draw::fill_rectangle(&mut self.image, top_left, width, height, color);
+    }
+
+    /// Draws the outline of a circle.
+    pub fn draw_circle(&mut self, center: (i32, i32), radius: u32, color: Rgb<u8>) {
+        draw::circle(&mut self.image, center, radius, color);
+    }
+
+    /// Fills a circle.
+    pub fn fill_circle(&mut self, center: (i32, i32), radius: u32, color: Rgb<u8>) {
+        draw::fill_circle(&mut self.image, center, radius, color);
+    }
+
+    /// Fills a polygon with the even-odd rule. The last point is connected to the first one.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], color: Rgb<u8>) {
+        draw::fill_polygon(&mut self.image, points, color);
+    }
+
+    /// Draws the set pixels of a bitmap. Unset pixels keep the background.
+    pub fn draw_bitmap(&mut self, top_left: (i32, i32), bitmap: &Bitmap, color: Rgb<u8>) {
+        draw::bitmap(&mut self.image, top_left, bitmap, color);
+    }
+
+    /// Draws text with its top left corner at `top_left`. Line breaks start a new line below.
+    pub fn draw_text(&mut self, top_left: (i32, i32), text: &str, font: &Font, color: Rgb<u8>) {
+        draw::text(&mut self.image, top_left, text, font, color);
+    }
+
+    /// Returns the conversion to black and white of 1-bpp displays.
+    pub fn get_dithering(&self) -> Dithering {
+        self.dithering
+    }
+
+    /// Sets the conversion to black and white of 1-bpp displays. Defaults to `Dithering::ChannelSum(0x30)`.
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        self.dithering = dithering;
+    }
+
+    /// Enables or disables partial updates of the framebuffer (enabled by default).
+    ///
+    /// With partial updates only the rows and columns that changed since the last `update()` are written to the framebuffer.
+    pub fn set_partial_updates(&mut self, partial_updates: bool) {
+        if let Backend::Framebuffer(output) = &mut self.backend {
+            output.partial_updates = partial_updates;
+        }
+    }
+
+    /// Forces the next `update()` to write the whole frame,
+    /// e.g. after another program or the console has drawn to the framebuffer.
+    pub fn invalidate(&mut self) {
+        if let Backend::Framebuffer(output) = &mut self.backend {
+            output.invalidate();
+        }
+    }
+
+    /// Checks if the screen draws to a hidden framebuffer page and flips pages on `update()`.
+    pub fn is_double_buffered(&self) -> bool {
+        match &self.backend {
+            Backend::Framebuffer(output) => output.double_buffered,
+            Backend::Headless(_) => false,
+        }
+    }
+
+    /// Enables or disables double buffering with page flipping to avoid tearing. Headless screens ignore this.
+    ///
+    /// The framebuffer has to provide a virtual resolution of at least twice the screen height.
+    /// If it does not, a larger virtual resolution is requested from the driver. Fails if the driver does not support this.
+    pub fn set_double_buffering(&mut self, double_buffered: bool) -> Ev3Result<()> {
+        match &mut self.backend {
+            Backend::Framebuffer(output) => output.set_double_buffering(double_buffered),
+            Backend::Headless(_) => Ok(()),
+        }
+    }
+
+    /// Applies pending changes to the screen.
+    /// Nothing will be drawn on the screen until this function is called.
+    pub fn update(&mut self) -> Ev3Result<()> {
+        match &mut self.backend {
+            Backend::Framebuffer(output) => output.update(&self.image, self.dithering),
+            Backend::Headless(renderer) => renderer.update(&self.image, self.dithering),
+        }
+    }
+}
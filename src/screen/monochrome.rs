@@ -0,0 +1,115 @@
+//! Conversion of color images to the monochrome display.
+
+use super::Bitmap;
+use image::{Rgb, RgbImage};
+
+/// Method to convert colors and gray levels to black and white.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dithering {
+    /// Pixels with a brightness below the threshold (0 to 255) are black. Keeps sharp edges of text and lines.
+    Threshold(u8),
+    /// Pixels with a sum of the red, green and blue values (0 to 765) below the threshold are black.
+    /// `ChannelSum(0x30)` is the default and the conversion of earlier versions, only very dark colors become black.
+    ChannelSum(u16),
+    /// Ordered dithering with a 4x4 Bayer matrix. Gray areas become regular patterns that do not change with animations.
+    Ordered,
+    /// Floyd–Steinberg error diffusion. Best reproduction of photos and gradients.
+    FloydSteinberg,
+}
+
+impl Default for Dithering {
+    fn default() -> Self {
+        Dithering::ChannelSum(0x30)
+    }
+}
+
+/// 4x4 Bayer matrix with thresholds 0 to 15.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Perceived brightness of a color in the range 0 to 255.
+pub fn brightness(pixel: &Rgb<u8>) -> u8 {
+    let [red, green, blue] = pixel.0;
+    ((299 * red as u32 + 587 * green as u32 + 114 * blue as u32) / 1000) as u8
+}
+
+/// Converts an image to black and white. Set pixels of the bitmap are black.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::{to_monochrome, Dithering};
+/// use image::{Rgb, RgbImage};
+///
+/// // A mid gray is black or white with a threshold, but a 50% pattern when dithered.
+/// let gray = RgbImage::from_pixel(8, 8, Rgb([128, 128, 128]));
+///
+/// let threshold = to_monochrome(&gray, Dithering::Threshold(200));
+/// assert!(threshold.get_pixel(0, 0) && threshold.get_pixel(1, 0));
+///
+/// // By default only very dark colors are black.
+/// let default = to_monochrome(&gray, Dithering::default());
+/// assert!(!default.get_pixel(0, 0));
+/// let dark = to_monochrome(&RgbImage::from_pixel(1, 1, Rgb([15, 15, 15])), Dithering::default());
+/// assert!(dark.get_pixel(0, 0));
+///
+/// for dithering in [Dithering::Ordered, Dithering::FloydSteinberg] {
+///     let bitmap = to_monochrome(&gray, dithering);
+///     let black = (0..8).flat_map(|y| (0..8).map(move |x| (x, y)))
+///         .filter(|&(x, y)| bitmap.get_pixel(x, y))
+///         .count();
+///     assert_eq!(black, 32);
+/// }
+/// ```
+pub fn to_monochrome(image: &RgbImage, dithering: Dithering) -> Bitmap {
+    let (width, height) = image.dimensions();
+    let mut bitmap = Bitmap::new(width, height);
+
+    match dithering {
+        Dithering::Threshold(threshold) => {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                bitmap.set_pixel(x, y, brightness(pixel) < threshold);
+            }
+        }
+        Dithering::ChannelSum(threshold) => {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let [red, green, blue] = pixel.0;
+                let sum = red as u16 + green as u16 + blue as u16;
+                bitmap.set_pixel(x, y, sum < threshold);
+            }
+        }
+        Dithering::Ordered => {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                let level = BAYER_4X4[(y % 4) as usize][(x % 4) as usize] as u32;
+                // Thresholds 8, 24, ..., 248 spread evenly over the brightness range.
+                let threshold = level * 16 + 8;
+                bitmap.set_pixel(x, y, (brightness(pixel) as u32) < threshold);
+            }
+        }
+        Dithering::FloydSteinberg => {
+            let width = width as usize;
+            let mut levels: Vec<f32> = image.pixels().map(|p| brightness(p) as f32).collect();
+
+            for index in 0..levels.len() {
+                let (x, y) = (index % width, index / width);
+                let old = levels[index];
+                let black = old < 128.0;
+                bitmap.set_pixel(x as u32, y as u32, black);
+
+                let error = old - if black { 0.0 } else { 255.0 };
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    if nx >= 0 && (nx as usize) < width {
+                        if let Some(level) = levels.get_mut((y + dy) * width + nx as usize) {
+                            *level += error * weight;
+                        }
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+
+    bitmap
+}
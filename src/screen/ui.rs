@@ -0,0 +1,610 @@
+//! Widgets for on-brick user interfaces, controlled with the buttons.
+//!
+//! A `Ui` combines a `Screen`, the `Button`s and a status bar with the battery level.
+//! `Ui::run()` shows a widget and processes button presses until the widget is finished.
+//!
+#![cfg_attr(feature = "ev3", doc = "```no_run")]
+#![cfg_attr(not(feature = "ev3"), doc = "```ignore")]
+//! use ev3dev_lang_rust::screen::ui::{Dialog, Menu, Spinner, Ui};
+//! use ev3dev_lang_rust::{Button, Screen};
+//!
+//! # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+//! let mut ui = Ui::new(Screen::new()?, Button::new()?);
+//! ui.set_title("Robot");
+//!
+//! let mut menu = Menu::new(&["Follow line", "Calibrate", "Settings"]);
+//! while let Some(program) = ui.run(&mut menu)? {
+//!     if program == 2 {
+//!         let speed = ui.run(&mut Spinner::new("Speed", 500, 0, 1000, 50))?;
+//!         println!("New speed: {:?}", speed);
+//!     } else if ui.run(&mut Dialog::new("Start program?"))? {
+//!         println!("Starting program {}", program);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{draw, Font, BLACK, WHITE};
+use crate::{Button, ButtonId, ButtonSet, Ev3Result, PowerSupply, Screen};
+use image::RgbImage;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Delay between two checks of the buttons in `Ui::run()`.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// Interval to refresh the battery level of the status bar.
+const STATUS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Rectangular area of the screen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rect {
+    /// Left edge.
+    pub x: i32,
+    /// Top edge.
+    pub y: i32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Returns the x coordinate to center content of the given width.
+    fn center_x(&self, width: u32) -> i32 {
+        self.x + (self.width as i32 - width as i32) / 2
+    }
+}
+
+/// Part of a user interface that is drawn into an area of the screen and reacts to button presses.
+pub trait Widget {
+    /// Result of the widget, e.g. the selected entry.
+    type Output;
+
+    /// Draws the widget into the given area of the image.
+    fn draw(&self, image: &mut RgbImage, area: Rect, font: &Font);
+
+    /// Handles a pressed button. Returns the result if the widget is finished.
+    fn handle_button(&mut self, button: ButtonId) -> Option<Self::Output>;
+}
+
+/// Draws text in a row of the area, inverted if `selected`.
+fn draw_row(image: &mut RgbImage, area: Rect, y: i32, text: &str, font: &Font, selected: bool) {
+    let height = font.get_height() + 2;
+    let color = if selected {
+        draw::fill_rectangle(image, (area.x, y), area.width, height, BLACK);
+        WHITE
+    } else {
+        BLACK
+    };
+    draw::text(image, (area.x + 2, y + 1), text, font, color);
+}
+
+/// Vertical list of entries. Up and down move the selection, enter selects and backspace cancels.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::ui::{Menu, Widget};
+/// use ev3dev_lang_rust::ButtonId;
+///
+/// let mut menu = Menu::new(&["Run", "Settings", "Exit"]);
+/// assert_eq!(menu.handle_button(ButtonId::Up), None);
+/// assert_eq!(menu.get_selected(), 2);
+/// assert_eq!(menu.handle_button(ButtonId::Enter), Some(Some(2)));
+/// assert_eq!(menu.handle_button(ButtonId::Backspace), Some(None));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+    items: Vec<String>,
+    selected: usize,
+    scroll: Cell<usize>,
+}
+
+impl Menu {
+    /// Create a menu with the given entries. The first entry is selected.
+    pub fn new<S: AsRef<str>>(items: &[S]) -> Self {
+        Menu {
+            items: items.iter().map(|item| item.as_ref().to_owned()).collect(),
+            selected: 0,
+            scroll: Cell::new(0),
+        }
+    }
+
+    /// Returns the entries.
+    pub fn get_items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Returns the index of the selected entry.
+    pub fn get_selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects the entry with the given index. Out of range indices select the last entry.
+    pub fn set_selected(&mut self, selected: usize) {
+        self.selected = selected.min(self.items.len().saturating_sub(1));
+    }
+}
+
+impl Widget for Menu {
+    /// Index of the selected entry or `None` if the menu was canceled.
+    type Output = Option<usize>;
+
+    fn draw(&self, image: &mut RgbImage, area: Rect, font: &Font) {
+        let row_height = font.get_height() + 2;
+        let rows = (area.height / row_height).max(1) as usize;
+
+        // Scroll just enough to keep the selection visible.
+        let mut scroll = self.scroll.get();
+        if self.selected < scroll {
+            scroll = self.selected;
+        } else if self.selected >= scroll + rows {
+            scroll = self.selected + 1 - rows;
+        }
+        self.scroll.set(scroll);
+
+        for (row, item) in self.items.iter().enumerate().skip(scroll).take(rows) {
+            let y = area.y + ((row - scroll) as u32 * row_height) as i32;
+            draw_row(image, area, y, item, font, row == self.selected);
+        }
+    }
+
+    fn handle_button(&mut self, button: ButtonId) -> Option<Self::Output> {
+        let count = self.items.len();
+        match button {
+            ButtonId::Up if count > 0 => self.selected = (self.selected + count - 1) % count,
+            ButtonId::Down if count > 0 => self.selected = (self.selected + 1) % count,
+            ButtonId::Enter if count > 0 => return Some(Some(self.selected)),
+            ButtonId::Backspace => return Some(None),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Numeric value within a range. Up and right increase, down and left decrease the value.
+/// Enter confirms and backspace cancels.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::ui::{Spinner, Widget};
+/// use ev3dev_lang_rust::ButtonId;
+///
+/// let mut spinner = Spinner::new("Speed", 90, 0, 100, 20);
+/// spinner.handle_button(ButtonId::Up);
+/// assert_eq!(spinner.get_value(), 100);
+/// spinner.handle_button(ButtonId::Left);
+/// assert_eq!(spinner.handle_button(ButtonId::Enter), Some(Some(80)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spinner {
+    label: String,
+    value: i32,
+    min: i32,
+    max: i32,
+    step: i32,
+}
+
+impl Spinner {
+    /// Create a spinner for values from `min` to `max` in steps of `step`.
+    pub fn new(label: &str, value: i32, min: i32, max: i32, step: i32) -> Self {
+        let (min, max) = (min.min(max), min.max(max));
+        Spinner {
+            label: label.to_owned(),
+            value: value.clamp(min, max),
+            min,
+            max,
+            step: step.max(1),
+        }
+    }
+
+    /// Returns the current value.
+    pub fn get_value(&self) -> i32 {
+        self.value
+    }
+}
+
+impl Widget for Spinner {
+    /// The value or `None` if the spinner was canceled.
+    type Output = Option<i32>;
+
+    fn draw(&self, image: &mut RgbImage, area: Rect, font: &Font) {
+        let row_height = (font.get_height() + 2) as i32;
+        draw::text(image, (area.x + 2, area.y + 1), &self.label, font, BLACK);
+
+        let text = format!("< {} >", self.value);
+        let width = font.text_width(&text);
+        let y = area.y + area.height as i32 / 2 - row_height / 2;
+        draw::rectangle(
+            image,
+            (area.center_x(width) - 3, y - 2),
+            width + 6,
+            row_height as u32 + 4,
+            BLACK,
+        );
+        draw::text(image, (area.center_x(width), y + 1), &text, font, BLACK);
+    }
+
+    fn handle_button(&mut self, button: ButtonId) -> Option<Self::Output> {
+        match button {
+            ButtonId::Up | ButtonId::Right => {
+                self.value = self.value.saturating_add(self.step).min(self.max)
+            }
+            ButtonId::Down | ButtonId::Left => {
+                self.value = self.value.saturating_sub(self.step).max(self.min)
+            }
+            ButtonId::Enter => return Some(Some(self.value)),
+            ButtonId::Backspace => return Some(None),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Yes/no question. Left and right switch the answer, enter confirms and backspace answers no.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::ui::{Dialog, Widget};
+/// use ev3dev_lang_rust::ButtonId;
+///
+/// let mut dialog = Dialog::new("Delete log?");
+/// assert_eq!(dialog.handle_button(ButtonId::Enter), Some(false));
+/// dialog.handle_button(ButtonId::Left);
+/// assert_eq!(dialog.handle_button(ButtonId::Enter), Some(true));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialog {
+    message: String,
+    yes: bool,
+}
+
+impl Dialog {
+    /// Create a dialog with the given question. Multiple lines are separated by `\n`. "No" is preselected.
+    pub fn new(message: &str) -> Self {
+        Dialog {
+            message: message.to_owned(),
+            yes: false,
+        }
+    }
+
+    /// Preselects "Yes" or "No".
+    pub fn set_default(&mut self, yes: bool) {
+        self.yes = yes;
+    }
+}
+
+impl Widget for Dialog {
+    /// `true` if "Yes" was confirmed.
+    type Output = bool;
+
+    fn draw(&self, image: &mut RgbImage, area: Rect, font: &Font) {
+        for (line, text) in self.message.lines().enumerate() {
+            let y = area.y + 2 + (line as u32 * font.get_height()) as i32;
+            draw::text(
+                image,
+                (area.center_x(font.text_width(text)), y),
+                text,
+                font,
+                BLACK,
+            );
+        }
+
+        let height = font.get_height() + 4;
+        let width = area.width / 3;
+        let y = area.y + area.height as i32 - height as i32 - 4;
+        for (index, (text, selected)) in [("Yes", self.yes), ("No", !self.yes)].iter().enumerate() {
+            let x = area.x + (area.width / 6 + index as u32 * area.width / 2) as i32;
+            let button = Rect {
+                x,
+                y,
+                width,
+                height,
+            };
+            draw::rectangle(image, (x, y), width, height, BLACK);
+            if *selected {
+                draw::fill_rectangle(image, (x, y), width, height, BLACK);
+            }
+            let color = if *selected { WHITE } else { BLACK };
+            draw::text(
+                image,
+                (button.center_x(font.text_width(text)), y + 2),
+                text,
+                font,
+                color,
+            );
+        }
+    }
+
+    fn handle_button(&mut self, button: ButtonId) -> Option<Self::Output> {
+        match button {
+            ButtonId::Left | ButtonId::Right | ButtonId::Up | ButtonId::Down => {
+                self.yes = !self.yes
+            }
+            ButtonId::Enter => return Some(self.yes),
+            ButtonId::Backspace => return Some(false),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Horizontal bar that shows the progress of a task. Backspace finishes it when used with `Ui::run()`.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::ui::{ProgressBar, Rect, Widget};
+/// use ev3dev_lang_rust::screen::{Font, WHITE};
+/// use image::RgbImage;
+///
+/// let mut image = RgbImage::from_pixel(178, 128, WHITE);
+/// let area = Rect { x: 0, y: 0, width: 178, height: 128 };
+/// ProgressBar::new("Loading").draw(&mut image, area, &Font::builtin());
+///
+/// // The frame of the bar spans (4, 64) to (173, 73), at 0% its inside is empty.
+/// for x in 5..173 {
+///     for y in 65..73 {
+///         assert_eq!(*image.get_pixel(x, y), WHITE);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressBar {
+    label: String,
+    progress: f32,
+}
+
+impl ProgressBar {
+    /// Create a progress bar with the given label at 0%.
+    pub fn new(label: &str) -> Self {
+        ProgressBar {
+            label: label.to_owned(),
+            progress: 0.0,
+        }
+    }
+
+    /// Returns the progress in the range 0 to 1.
+    pub fn get_progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Sets the progress. The value is clamped to the range 0 to 1.
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = progress.clamp(0.0, 1.0);
+    }
+
+    /// Sets the label.
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_owned();
+    }
+}
+
+impl Widget for ProgressBar {
+    type Output = ();
+
+    fn draw(&self, image: &mut RgbImage, area: Rect, font: &Font) {
+        let y = area.y + area.height as i32 / 2 - font.get_height() as i32;
+        draw::text(image, (area.x + 4, y - 2), &self.label, font, BLACK);
+
+        let width = area.width.saturating_sub(8);
+        let bar_y = y + font.get_height() as i32;
+        draw::rectangle(image, (area.x + 4, bar_y), width, 10, BLACK);
+        let filled = (width.saturating_sub(4) as f32 * self.progress).round() as u32;
+        draw::fill_rectangle(image, (area.x + 6, bar_y + 2), filled, 6, BLACK);
+
+        let percent = format!("{:.0}%", self.progress * 100.0);
+        let x = area.center_x(font.text_width(&percent));
+        draw::text(image, (x, bar_y + 12), &percent, font, BLACK);
+    }
+
+    fn handle_button(&mut self, button: ButtonId) -> Option<Self::Output> {
+        (button == ButtonId::Backspace).then_some(())
+    }
+}
+
+/// Line at the top of the screen with a title and the battery level.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::screen::ui::StatusBar;
+///
+/// // Li-ion battery between 7.1V and 8.4V.
+/// assert_eq!(StatusBar::battery_level(7_750_000, 7_100_000, 8_400_000), 0.5);
+/// assert_eq!(StatusBar::battery_level(9_000_000, 7_100_000, 8_400_000), 1.0);
+/// ```
+///
+/// An empty battery is drawn as an empty frame:
+/// ```
+/// use ev3dev_lang_rust::screen::ui::StatusBar;
+/// use ev3dev_lang_rust::screen::{Font, WHITE};
+/// use image::RgbImage;
+///
+/// let mut image = RgbImage::from_pixel(178, 128, WHITE);
+/// let status_bar = StatusBar { title: String::new(), battery: Some(0.0) };
+/// status_bar.draw(&mut image, &Font::builtin());
+///
+/// // The battery frame spans (158, 1) to (173, 6).
+/// for x in 159..173 {
+///     for y in 2..6 {
+///         assert_eq!(*image.get_pixel(x, y), WHITE);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusBar {
+    /// Text on the left side.
+    pub title: String,
+    /// Battery level in the range 0 to 1, not shown if `None`.
+    pub battery: Option<f32>,
+}
+
+impl StatusBar {
+    /// Returns the height of the status bar including the separator line.
+    pub fn get_height(font: &Font) -> u32 {
+        font.get_height() + 3
+    }
+
+    /// Converts a voltage to a battery level in the range 0 to 1 between the design voltages (all in microvolts).
+    pub fn battery_level(voltage: i32, min_voltage: i32, max_voltage: i32) -> f32 {
+        let range = (max_voltage - min_voltage).max(1) as f32;
+        ((voltage - min_voltage) as f32 / range).clamp(0.0, 1.0)
+    }
+
+    /// Reads the battery level from the power supply.
+    pub fn read_battery(power_supply: &PowerSupply) -> Ev3Result<f32> {
+        Ok(StatusBar::battery_level(
+            power_supply.get_voltage_now()?,
+            power_supply.get_voltage_min_design()?,
+            power_supply.get_voltage_max_design()?,
+        ))
+    }
+
+    /// Draws the status bar at the top of the image.
+    pub fn draw(&self, image: &mut RgbImage, font: &Font) {
+        let height = StatusBar::get_height(font) as i32;
+        draw::text(image, (2, 1), &self.title, font, BLACK);
+        draw::line(
+            image,
+            (0, height - 1),
+            (image.width() as i32 - 1, height - 1),
+            BLACK,
+        );
+
+        if let Some(battery) = self.battery {
+            // Battery icon with a tip on the right side, filled by the level.
+            let (width, icon_height) = (16, font.get_height().saturating_sub(2).max(4));
+            let x = image.width() as i32 - width as i32 - 4;
+            draw::rectangle(image, (x, 1), width, icon_height, BLACK);
+            draw::fill_rectangle(image, (x + width as i32, 3), 2, icon_height - 4, BLACK);
+            let filled = ((width - 4) as f32 * battery).round() as u32;
+            draw::fill_rectangle(image, (x + 2, 3), filled, icon_height - 4, BLACK);
+
+            let percent = format!("{:.0}%", battery * 100.0);
+            let text_x = x - font.text_width(&percent) as i32 - 2;
+            draw::text(image, (text_x, 1), &percent, font, BLACK);
+        }
+    }
+}
+
+/// Screen with status bar and button event loop to show widgets.
+#[derive(Debug)]
+pub struct Ui {
+    screen: Screen,
+    button: Button,
+    font: Font,
+    status_bar: StatusBar,
+    power_supply: Option<PowerSupply>,
+    pressed: Rc<RefCell<VecDeque<ButtonId>>>,
+}
+
+impl Ui {
+    /// Create a user interface with the built-in font.
+    /// Replaces the change handler of the buttons to receive button presses.
+    /// The battery level is shown if the power supply of the brick is found.
+    pub fn new(screen: Screen, mut button: Button) -> Self {
+        let pressed = Rc::new(RefCell::new(VecDeque::new()));
+
+        let queue = pressed.clone();
+        let previous = Cell::new(ButtonSet::new());
+        button.set_change_handler(move |buttons: ButtonSet| {
+            // Only newly pressed buttons are events, releases are ignored.
+            let changed = buttons.symmetric_difference(&previous.get());
+            queue
+                .borrow_mut()
+                .extend(changed.iter().filter(|button| buttons.contains(*button)));
+            previous.set(buttons);
+        });
+
+        Ui {
+            screen,
+            button,
+            font: Font::builtin(),
+            status_bar: StatusBar {
+                title: String::new(),
+                battery: None,
+            },
+            power_supply: PowerSupply::new().ok(),
+            pressed,
+        }
+    }
+
+    /// Sets the title of the status bar.
+    pub fn set_title(&mut self, title: &str) {
+        self.status_bar.title = title.to_owned();
+    }
+
+    /// Sets the font of the status bar and all widgets.
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+    }
+
+    /// Sets the power supply of the battery level. `None` hides the battery level.
+    pub fn set_power_supply(&mut self, power_supply: Option<PowerSupply>) {
+        self.power_supply = power_supply;
+    }
+
+    /// Returns the screen, e.g. to draw between widgets.
+    pub fn get_screen_mut(&mut self) -> &mut Screen {
+        &mut self.screen
+    }
+
+    /// Returns the buttons.
+    pub fn get_button(&self) -> &Button {
+        &self.button
+    }
+
+    /// Returns the area below the status bar.
+    pub fn get_content_area(&self) -> Rect {
+        let top = StatusBar::get_height(&self.font);
+        Rect {
+            x: 0,
+            y: top as i32,
+            width: self.screen.xres(),
+            height: self.screen.yres().saturating_sub(top),
+        }
+    }
+
+    /// Draws the status bar and the widget without waiting for buttons, e.g. to update a `ProgressBar`.
+    pub fn show<W: Widget>(&mut self, widget: &W) -> Ev3Result<()> {
+        if let Some(power_supply) = &self.power_supply {
+            self.status_bar.battery = StatusBar::read_battery(power_supply).ok();
+        }
+
+        let area = self.get_content_area();
+        self.screen.clear();
+        self.status_bar.draw(&mut self.screen.image, &self.font);
+        widget.draw(&mut self.screen.image, area, &self.font);
+        self.screen.update()
+    }
+
+    /// Shows the widget and passes button presses to it until it returns its result.
+    pub fn run<W: Widget>(&mut self, widget: &mut W) -> Ev3Result<W::Output> {
+        // Discard presses that happened before the widget was shown.
+        self.button.process();
+        self.pressed.borrow_mut().clear();
+
+        self.show(widget)?;
+        let mut last_draw = Instant::now();
+
+        loop {
+            self.button.process();
+            let pressed: Vec<ButtonId> = self.pressed.borrow_mut().drain(..).collect();
+
+            for button in &pressed {
+                if let Some(output) = widget.handle_button(*button) {
+                    return Ok(output);
+                }
+            }
+
+            if !pressed.is_empty() || last_draw.elapsed() >= STATUS_INTERVAL {
+                self.show(widget)?;
+                last_draw = Instant::now();
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! White/black calibration and nearest-neighbour classification of `RGB-RAW` colors.
+
+use super::ColorSensor;
+use crate::{Ev3Error, Ev3Result};
+use std::fs;
+use std::path::Path;
+
+/// Color with red, green and blue components in the range 0 to 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rgb {
+    /// Red component.
+    pub red: f32,
+    /// Green component.
+    pub green: f32,
+    /// Blue component.
+    pub blue: f32,
+}
+
+/// Color as hue, saturation and value.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hsv {
+    /// Hue in degrees, in the range 0 to 360. `0` for gray colors.
+    pub hue: f32,
+    /// Saturation in the range 0 to 1.
+    pub saturation: f32,
+    /// Value (brightness) in the range 0 to 1.
+    pub value: f32,
+}
+
+impl Rgb {
+    /// Converts the color to hue, saturation and value.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::Rgb;
+    ///
+    /// let hsv = Rgb { red: 0.0, green: 0.5, blue: 0.5 }.to_hsv();
+    /// assert_eq!(hsv.hue, 180.0);
+    /// assert_eq!(hsv.saturation, 1.0);
+    /// assert_eq!(hsv.value, 0.5);
+    /// ```
+    pub fn to_hsv(self) -> Hsv {
+        let max = self.red.max(self.green).max(self.blue);
+        let min = self.red.min(self.green).min(self.blue);
+        let delta = max - min;
+
+        let hue = if delta <= f32::EPSILON {
+            0.0
+        } else if max == self.red {
+            60.0 * ((self.green - self.blue) / delta).rem_euclid(6.0)
+        } else if max == self.green {
+            60.0 * ((self.blue - self.red) / delta + 2.0)
+        } else {
+            60.0 * ((self.red - self.green) / delta + 4.0)
+        };
+
+        Hsv {
+            hue,
+            saturation: if max <= f32::EPSILON {
+                0.0
+            } else {
+                delta / max
+            },
+            value: max,
+        }
+    }
+
+    fn distance(&self, other: &Rgb) -> f32 {
+        ((self.red - other.red).powi(2)
+            + (self.green - other.green).powi(2)
+            + (self.blue - other.blue).powi(2))
+        .sqrt()
+    }
+}
+
+/// Raw `RGB-RAW` readings of a white and a black reference surface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ColorCalibration {
+    /// Reading of the white reference.
+    pub white: (i32, i32, i32),
+    /// Reading of the black reference.
+    pub black: (i32, i32, i32),
+}
+
+impl Default for ColorCalibration {
+    /// Uncalibrated: the full raw range 0 to 1020.
+    fn default() -> Self {
+        ColorCalibration {
+            white: (1020, 1020, 1020),
+            black: (0, 0, 0),
+        }
+    }
+}
+
+impl ColorCalibration {
+    /// Measures the white reference with the sensor in `RGB-RAW` mode.
+    pub fn measure_white(&mut self, sensor: &ColorSensor, samples: usize) -> Ev3Result<()> {
+        self.white = sensor.get_rgb_averaged(samples)?;
+        Ok(())
+    }
+
+    /// Measures the black reference with the sensor in `RGB-RAW` mode.
+    pub fn measure_black(&mut self, sensor: &ColorSensor, samples: usize) -> Ev3Result<()> {
+        self.black = sensor.get_rgb_averaged(samples)?;
+        Ok(())
+    }
+
+    /// Scales a raw reading so black is `0` and white is `1`. The result is clamped to this range.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::ColorCalibration;
+    ///
+    /// let calibration = ColorCalibration { white: (300, 400, 200), black: (20, 40, 0) };
+    /// let rgb = calibration.normalize((160, 400, 250));
+    /// assert_eq!((rgb.red, rgb.green, rgb.blue), (0.5, 1.0, 1.0));
+    /// ```
+    pub fn normalize(&self, raw: (i32, i32, i32)) -> Rgb {
+        let scale = |value: i32, black: i32, white: i32| {
+            let range = (white - black).max(1) as f32;
+            ((value - black) as f32 / range).clamp(0.0, 1.0)
+        };
+        Rgb {
+            red: scale(raw.0, self.black.0, self.white.0),
+            green: scale(raw.1, self.black.1, self.white.1),
+            blue: scale(raw.2, self.black.2, self.white.2),
+        }
+    }
+
+    /// Reads the sensor in `RGB-RAW` mode and returns the normalized color.
+    pub fn read(&self, sensor: &ColorSensor) -> Ev3Result<Rgb> {
+        Ok(self.normalize(sensor.get_rgb()?))
+    }
+}
+
+/// Classifies calibrated colors by their nearest trained sample.
+///
+/// The classifier stores the calibration together with labeled samples of normalized colors,
+/// so custom surface colors can be trained once and loaded again with `load()`.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sensors::{ColorClassifier, ColorSensor};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let sensor = ColorSensor::find()?;
+/// sensor.set_mode_rgb_raw()?;
+///
+/// let mut classifier = ColorClassifier::new();
+/// // Place the sensor over each surface before the corresponding call.
+/// classifier.get_calibration_mut().measure_white(&sensor, 10)?;
+/// classifier.get_calibration_mut().measure_black(&sensor, 10)?;
+/// classifier.train_from_sensor(&sensor, "table", 10)?;
+/// classifier.train_from_sensor(&sensor, "orange tape", 10)?;
+/// classifier.save("colors.txt")?;
+///
+/// let classifier = ColorClassifier::load("colors.txt")?;
+/// println!("Surface: {:?}", classifier.classify_sensor(&sensor)?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColorClassifier {
+    calibration: ColorCalibration,
+    samples: Vec<(String, Rgb)>,
+    max_distance: Option<f32>,
+}
+
+impl ColorClassifier {
+    /// Create an uncalibrated classifier without samples.
+    pub fn new() -> Self {
+        ColorClassifier::default()
+    }
+
+    /// Returns the calibration.
+    pub fn get_calibration(&self) -> &ColorCalibration {
+        &self.calibration
+    }
+
+    /// Returns the calibration for modification.
+    /// Samples trained with a different calibration should be trained again.
+    pub fn get_calibration_mut(&mut self) -> &mut ColorCalibration {
+        &mut self.calibration
+    }
+
+    /// Sets the maximal distance between a color and its nearest sample.
+    /// Colors further away are not classified. `None` always returns the nearest sample.
+    pub fn set_max_distance(&mut self, max_distance: Option<f32>) {
+        self.max_distance = max_distance;
+    }
+
+    /// Returns all trained samples.
+    pub fn get_samples(&self) -> &[(String, Rgb)] {
+        &self.samples
+    }
+
+    /// Adds a normalized sample color with the given label.
+    ///
+    /// Runs of whitespace and line breaks in the label are replaced by single spaces, so it can be saved.
+    /// Fails if the label is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::{ColorClassifier, Rgb};
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut classifier = ColorClassifier::new();
+    /// classifier.train(" light  blue\ntape ", Rgb { red: 0.2, green: 0.6, blue: 0.9 })?;
+    /// assert_eq!(classifier.get_samples()[0].0, "light blue tape");
+    /// assert!(classifier.train(" \n", Rgb { red: 0.2, green: 0.6, blue: 0.9 }).is_err());
+    ///
+    /// let loaded = ColorClassifier::from_text(&classifier.to_text())?;
+    /// assert_eq!(loaded, classifier);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn train(&mut self, label: &str, color: Rgb) -> Ev3Result<()> {
+        let label = normalize_label(label);
+        if label.is_empty() {
+            return Err(Ev3Error::InternalError {
+                msg: "Color label must not be empty".to_owned(),
+            });
+        }
+        self.samples.push((label, color));
+        Ok(())
+    }
+
+    /// Reads the mean of `samples` readings of the sensor in `RGB-RAW` mode and adds it with the given label.
+    pub fn train_from_sensor(
+        &mut self,
+        sensor: &ColorSensor,
+        label: &str,
+        samples: usize,
+    ) -> Ev3Result<()> {
+        let color = self
+            .calibration
+            .normalize(sensor.get_rgb_averaged(samples)?);
+        self.train(label, color)
+    }
+
+    /// Removes all samples with the given label.
+    pub fn forget(&mut self, label: &str) {
+        let label = normalize_label(label);
+        self.samples
+            .retain(|(sample_label, _)| *sample_label != label);
+    }
+
+    /// Returns the label of the nearest sample of a normalized color.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::{ColorClassifier, Rgb};
+    ///
+    /// let mut classifier = ColorClassifier::new();
+    /// classifier.train("red", Rgb { red: 0.8, green: 0.1, blue: 0.1 }).unwrap();
+    /// classifier.train("orange", Rgb { red: 0.9, green: 0.5, blue: 0.1 }).unwrap();
+    ///
+    /// assert_eq!(classifier.classify(Rgb { red: 0.85, green: 0.4, blue: 0.1 }), Some("orange"));
+    ///
+    /// classifier.set_max_distance(Some(0.1));
+    /// assert_eq!(classifier.classify(Rgb { red: 0.1, green: 0.1, blue: 0.9 }), None);
+    /// ```
+    pub fn classify(&self, color: Rgb) -> Option<&str> {
+        self.samples
+            .iter()
+            .map(|(label, sample)| (label, sample.distance(&color)))
+            .filter(|(_, distance)| self.max_distance.is_none_or(|max| *distance <= max))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Reads the sensor in `RGB-RAW` mode and classifies the calibrated color.
+    pub fn classify_sensor(&self, sensor: &ColorSensor) -> Ev3Result<Option<String>> {
+        let color = self.calibration.read(sensor)?;
+        Ok(self.classify(color).map(str::to_owned))
+    }
+
+    /// Serializes calibration and samples as text, one entry per line.
+    pub fn to_text(&self) -> String {
+        let (white, black) = (self.calibration.white, self.calibration.black);
+        let mut text = format!(
+            "white {} {} {}\nblack {} {} {}\n",
+            white.0, white.1, white.2, black.0, black.1, black.2
+        );
+        if let Some(max_distance) = self.max_distance {
+            text += &format!("max_distance {max_distance}\n");
+        }
+        for (label, color) in &self.samples {
+            text += &format!(
+                "sample {} {} {} {label}\n",
+                color.red, color.green, color.blue
+            );
+        }
+        text
+    }
+
+    /// Parses the text format of `to_text()`.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::{ColorClassifier, Rgb};
+    ///
+    /// let mut classifier = ColorClassifier::new();
+    /// classifier.train("light blue", Rgb { red: 0.2, green: 0.6, blue: 0.9 }).unwrap();
+    ///
+    /// let loaded = ColorClassifier::from_text(&classifier.to_text()).unwrap();
+    /// assert_eq!(loaded, classifier);
+    ///
+    /// // Columns may be separated by tabs or several spaces.
+    /// let edited = ColorClassifier::from_text("sample\t0.2  0.6 0.9   light blue\n").unwrap();
+    /// assert_eq!(edited, classifier);
+    /// ```
+    pub fn from_text(text: &str) -> Ev3Result<ColorClassifier> {
+        let mut classifier = ColorClassifier::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || Ev3Error::InternalError {
+                msg: format!("Invalid color calibration in line {}: {line}", number + 1),
+            };
+
+            let mut parts = line.split_whitespace();
+            let key = parts.next().ok_or_else(invalid)?;
+            let mut next_number = || -> Ev3Result<f32> {
+                parts
+                    .next()
+                    .and_then(|part| part.parse().ok())
+                    .ok_or_else(invalid)
+            };
+
+            match key {
+                "white" | "black" => {
+                    let raw = (
+                        next_number()? as i32,
+                        next_number()? as i32,
+                        next_number()? as i32,
+                    );
+                    if key == "white" {
+                        classifier.calibration.white = raw;
+                    } else {
+                        classifier.calibration.black = raw;
+                    }
+                }
+                "max_distance" => classifier.max_distance = Some(next_number()?),
+                "sample" => {
+                    let color = Rgb {
+                        red: next_number()?,
+                        green: next_number()?,
+                        blue: next_number()?,
+                    };
+                    // Labels may contain spaces, runs of whitespace are collapsed.
+                    let label = parts.collect::<Vec<_>>().join(" ");
+                    classifier.train(&label, color).map_err(|_| invalid())?;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(classifier)
+    }
+
+    /// Writes calibration and samples to a text file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Ev3Result<()> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// Reads calibration and samples from a text file written by `save()`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Ev3Result<ColorClassifier> {
+        ColorClassifier::from_text(&fs::read_to_string(path)?)
+    }
+}
+
+/// Replaces runs of whitespace and line breaks by single spaces and trims the label.
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ")
+}
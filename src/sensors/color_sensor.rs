@@ -2,6 +2,68 @@
 
 use super::{Sensor, SensorPort};
 use crate::{sensor_mode, Attribute, Device, Driver, Ev3Error, Ev3Result};
+use std::thread;
+use std::time::Duration;
+
+/// Colors detected in the `COL-COLOR` mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// No color detected (code 0).
+    NoColor,
+    /// Black (code 1).
+    Black,
+    /// Blue (code 2).
+    Blue,
+    /// Green (code 3).
+    Green,
+    /// Yellow (code 4).
+    Yellow,
+    /// Red (code 5).
+    Red,
+    /// White (code 6).
+    White,
+    /// Brown (code 7).
+    Brown,
+}
+
+impl Color {
+    /// Converts a `COL-COLOR` code to a color. Unknown codes are mapped to `NoColor`.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::Color;
+    ///
+    /// assert_eq!(Color::from_code(5), Color::Red);
+    /// assert_eq!(Color::from_code(5).code(), 5);
+    /// assert_eq!(Color::from_code(42), Color::NoColor);
+    /// ```
+    pub fn from_code(code: i32) -> Color {
+        match code {
+            1 => Color::Black,
+            2 => Color::Blue,
+            3 => Color::Green,
+            4 => Color::Yellow,
+            5 => Color::Red,
+            6 => Color::White,
+            7 => Color::Brown,
+            _ => Color::NoColor,
+        }
+    }
+
+    /// Returns the `COL-COLOR` code of the color.
+    pub fn code(self) -> i32 {
+        match self {
+            Color::NoColor => 0,
+            Color::Black => 1,
+            Color::Blue => 2,
+            Color::Green => 3,
+            Color::Yellow => 4,
+            Color::Red => 5,
+            Color::White => 6,
+            Color::Brown => 7,
+        }
+    }
+}
 
 /// LEGO EV3 color sensor.
 #[derive(Debug, Clone, Device, Sensor)]
@@ -93,4 +155,32 @@ impl ColorSensor {
 
         Ok((red, green, blue))
     }
+
+    /// Get the detected color in `COL-COLOR` mode.
+    /// Fails if it has been set in the wrong mode.
+    pub fn get_named_color(&self) -> Ev3Result<Color> {
+        if !self.is_mode_col_color()? {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Cannot get named color while in {} mode", self.get_mode()?),
+            });
+        }
+        Ok(Color::from_code(self.get_value0()?))
+    }
+
+    /// Mean of `samples` consecutive `RGB-RAW` readings, to reduce noise during calibration.
+    /// Waits one poll period between the readings, so each reading is a new sample of the sensor.
+    pub fn get_rgb_averaged(&self, samples: usize) -> Ev3Result<(i32, i32, i32)> {
+        let samples = samples.max(1);
+        let poll_interval = Duration::from_millis(self.get_poll_ms().unwrap_or(10).max(1) as u64);
+        let mut sum = (0, 0, 0);
+        for i in 0..samples {
+            if i > 0 {
+                thread::sleep(poll_interval);
+            }
+            let (red, green, blue) = self.get_rgb()?;
+            sum = (sum.0 + red, sum.1 + green, sum.2 + blue);
+        }
+        let n = samples as i32;
+        Ok((sum.0 / n, sum.1 / n, sum.2 / n))
+    }
 }
@@ -1,7 +1,13 @@
 //! HiTechnic EV3 / NXT Compass Sensor. (<https://www.generationrobots.com/en/401186-hitechnic-compass-sensor-for-lego-mindstorms-nxt-and-ev3.html>)
 
 use super::{Sensor, SensorPort};
+use crate::filter::Filter;
+use crate::motors::TachoMotor;
 use crate::{Attribute, Device, Driver, Ev3Error, Ev3Result};
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 /// HiTechnic EV3 / NXT Compass Sensor.
 #[derive(Debug, Clone, Device, Sensor)]
@@ -67,4 +73,301 @@ impl CompassSensor {
     pub fn stop_calibration(&self) -> Ev3Result<()> {
         self.set_command(Self::COMMAND_STOP_CALIBRATION)
     }
+
+    /// Returns the origin / the zero point in degrees.
+    pub fn get_zero(&self) -> i32 {
+        self.origin
+    }
+
+    /// Sets the origin / the zero point in degrees, e.g. a value stored with `save_zero()`.
+    pub fn set_zero_to(&mut self, origin: i32) {
+        self.origin = origin.rem_euclid(360);
+    }
+
+    /// Writes the origin to a text file, so it can be restored with `load_zero()` after a restart.
+    pub fn save_zero<P: AsRef<Path>>(&self, path: P) -> Ev3Result<()> {
+        fs::write(path, format!("{}\n", self.origin))?;
+        Ok(())
+    }
+
+    /// Reads the origin from a text file written by `save_zero()`.
+    pub fn load_zero<P: AsRef<Path>>(&mut self, path: P) -> Ev3Result<()> {
+        let origin = fs::read_to_string(path)?.trim().parse()?;
+        self.set_zero_to(origin);
+        Ok(())
+    }
+
+    /// Guided calibration: turns a differential drive base on the spot while the sensor is in calibration mode.
+    ///
+    /// The `left` and `right` motors run with `speed_sp` in opposite directions for `duration`.
+    /// HiTechnic recommends one and a half to two slow turns, so choose `duration` accordingly.
+    /// The motors are stopped and the calibration is ended even if an error occurs.
+    /// Returns an error if the sensor reports an invalid heading after the calibration.
+    ///
+    /// ```no_run
+    /// use ev3dev_lang_rust::motors::{LargeMotor, MotorPort};
+    /// use ev3dev_lang_rust::sensors::CompassSensor;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut compass = CompassSensor::find()?;
+    /// let left = LargeMotor::get(MotorPort::OutB)?.into();
+    /// let right = LargeMotor::get(MotorPort::OutC)?.into();
+    ///
+    /// compass.calibrate_with_motors(&left, &right, 100, Duration::from_secs(30))?;
+    /// compass.set_zero()?;
+    /// compass.save_zero("compass_zero.txt")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn calibrate_with_motors(
+        &self,
+        left: &TachoMotor,
+        right: &TachoMotor,
+        speed_sp: i32,
+        duration: Duration,
+    ) -> Ev3Result<()> {
+        let turn = || -> Ev3Result<()> {
+            left.set_speed_sp(speed_sp)?;
+            right.set_speed_sp(-speed_sp)?;
+            left.run_forever()?;
+            right.run_forever()?;
+            thread::sleep(duration);
+            Ok(())
+        };
+
+        let result = self.start_calibration().and_then(|_| turn());
+
+        let stopped = left.stop().and(right.stop());
+        let ended = self.stop_calibration();
+        result.and(stopped).and(ended)?;
+
+        // Give the sensor time to store the calibration.
+        thread::sleep(Duration::from_millis(100));
+
+        let rotation = self.get_rotation()?;
+        if !(0..360).contains(&rotation) {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Compass calibration failed, sensor reports {rotation}"),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Smoothed compass heading relative to the zero point.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CompassReading {
+    /// Smoothed heading in degrees, in the range 0 to 360.
+    pub heading: f32,
+    /// `true` if the raw reading is invalid or jumped by more than the interference threshold.
+    /// Tilting the sensor and magnetic fields of motors or metal cause such jumps.
+    pub interference: bool,
+}
+
+/// Smooths compass headings, independent of the sensor.
+///
+/// The headings are filtered as unit vectors, so the wrap-around from 359 to 0 degrees does not disturb the filter.
+/// A heading that jumps by more than the interference threshold is reported but not filtered.
+/// If the following headings confirm the jump, e.g. after a fast turn, the filter is reset to the new heading.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::filter::Filter;
+/// use ev3dev_lang_rust::sensors::HeadingFilter;
+///
+/// let mut filter = HeadingFilter::new(Filter::exponential(0.5));
+/// filter.update(Some(350.0));
+/// // The wrap-around is smoothed to 10 degrees instead of 190 degrees.
+/// assert_eq!(filter.update(Some(30.0)).heading.round(), 10.0);
+///
+/// // A fast turn by 90 degrees is rejected twice, then the filter follows.
+/// assert!(filter.update(Some(90.0)).interference);
+/// assert!(filter.update(Some(91.0)).interference);
+/// let reading = filter.update(Some(90.0));
+/// assert!(!reading.interference);
+/// assert_eq!(reading.heading.round(), 90.0);
+///
+/// // A single outlier is rejected.
+/// assert!(filter.update(Some(200.0)).interference);
+/// assert_eq!(filter.update(Some(90.0)).heading.round(), 90.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeadingFilter {
+    x: Filter,
+    y: Filter,
+    interference_threshold: f32,
+    max_rejections: u32,
+    rejections: u32,
+    /// Last rejected heading, later rejections have to agree with it to be counted.
+    candidate: Option<f32>,
+}
+
+impl HeadingFilter {
+    /// Create a heading filter. The `filter` is used for both components of the heading vector.
+    pub fn new(filter: Filter) -> Self {
+        HeadingFilter {
+            x: filter.clone(),
+            y: filter,
+            interference_threshold: 45.0,
+            max_rejections: 2,
+            rejections: 0,
+            candidate: None,
+        }
+    }
+
+    /// Sets the maximal change of the heading in degrees between two readings that is not reported as interference.
+    /// Defaults to 45 degrees.
+    pub fn set_interference_threshold(&mut self, threshold: f32) {
+        self.interference_threshold = threshold;
+    }
+
+    /// Sets the number of consecutive, agreeing jumps that are rejected before the filter follows them.
+    /// Defaults to 2.
+    pub fn set_max_rejections(&mut self, max_rejections: u32) {
+        self.max_rejections = max_rejections;
+    }
+
+    /// Adds a heading in degrees (0 to 360) and returns the smoothed heading.
+    /// `None` marks an invalid reading, which is reported as interference.
+    pub fn update(&mut self, heading: Option<f32>) -> CompassReading {
+        let previous = self.get_heading();
+
+        let heading = match heading {
+            Some(heading) => heading,
+            None => {
+                return CompassReading {
+                    heading: previous.unwrap_or(0.0),
+                    interference: true,
+                }
+            }
+        };
+
+        if let Some(previous) = previous {
+            if angle_difference(heading, previous).abs() > self.interference_threshold {
+                let agrees = self.candidate.is_some_and(|candidate| {
+                    angle_difference(heading, candidate).abs() <= self.interference_threshold
+                });
+                self.rejections = if agrees { self.rejections + 1 } else { 1 };
+                self.candidate = Some(heading);
+
+                if self.rejections <= self.max_rejections {
+                    return CompassReading {
+                        heading: previous,
+                        interference: true,
+                    };
+                }
+                // The jump is confirmed, start over at the new heading.
+                self.x.reset();
+                self.y.reset();
+            }
+        }
+        self.rejections = 0;
+        self.candidate = None;
+
+        let radians = heading.to_radians();
+        self.x.update(radians.cos());
+        self.y.update(radians.sin());
+
+        CompassReading {
+            heading: self.get_heading().unwrap_or(heading),
+            interference: false,
+        }
+    }
+
+    /// Returns the current smoothed heading in degrees (0 to 360) or `None` before the first valid reading.
+    pub fn get_heading(&self) -> Option<f32> {
+        let x = self.x.get_value()?;
+        let y = self.y.get_value()?;
+        Some(y.atan2(x).to_degrees().rem_euclid(360.0))
+    }
+
+    /// Discards all headings.
+    pub fn reset(&mut self) {
+        self.x.reset();
+        self.y.reset();
+        self.rejections = 0;
+        self.candidate = None;
+    }
+}
+
+/// Signed difference of two angles in degrees, in the range -180 to 180.
+fn angle_difference(a: f32, b: f32) -> f32 {
+    (a - b + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// Smooths the heading of a compass sensor with a `HeadingFilter`.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::filter::Filter;
+/// use ev3dev_lang_rust::sensors::{CompassSensor, SmoothedCompass};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut compass = CompassSensor::find()?;
+/// compass.load_zero("compass_zero.txt")?;
+///
+/// let mut smoothed = SmoothedCompass::new(compass, Filter::exponential(0.3));
+/// loop {
+///     let reading = smoothed.update()?;
+///     if reading.interference {
+///         println!("Warning: tilt or magnetic interference");
+///     }
+///     println!("Heading: {:.1}", reading.heading);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SmoothedCompass {
+    sensor: CompassSensor,
+    filter: HeadingFilter,
+}
+
+impl SmoothedCompass {
+    /// Wrap a compass sensor. The `filter` is used for both components of the heading vector.
+    pub fn new(sensor: CompassSensor, filter: Filter) -> Self {
+        SmoothedCompass {
+            sensor,
+            filter: HeadingFilter::new(filter),
+        }
+    }
+
+    /// Returns the wrapped sensor.
+    pub fn get_sensor(&self) -> &CompassSensor {
+        &self.sensor
+    }
+
+    /// Returns the wrapped sensor for modification, e.g. to set the zero point.
+    /// The filter is reset.
+    pub fn get_sensor_mut(&mut self) -> &mut CompassSensor {
+        self.filter.reset();
+        &mut self.sensor
+    }
+
+    /// Sets the maximal change of the heading in degrees between two readings that is not reported as interference.
+    /// Defaults to 45 degrees.
+    pub fn set_interference_threshold(&mut self, threshold: f32) {
+        self.filter.set_interference_threshold(threshold);
+    }
+
+    /// Sets the number of consecutive, agreeing jumps that are rejected before the heading follows them.
+    /// Defaults to 2.
+    pub fn set_max_rejections(&mut self, max_rejections: u32) {
+        self.filter.set_max_rejections(max_rejections);
+    }
+
+    /// Reads the sensor and returns the smoothed heading relative to the zero point.
+    pub fn update(&mut self) -> Ev3Result<CompassReading> {
+        let rotation = self.sensor.get_rotation()?;
+        let heading = if (0..360).contains(&rotation) {
+            Some((rotation - self.sensor.get_zero()).rem_euclid(360) as f32)
+        } else {
+            None
+        };
+        Ok(self.filter.update(heading))
+    }
+
+    /// Returns the current smoothed heading in degrees (0 to 360) or `None` before the first valid reading.
+    pub fn get_heading(&self) -> Option<f32> {
+        self.filter.get_heading()
+    }
 }
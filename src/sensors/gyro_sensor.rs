@@ -2,6 +2,17 @@
 
 use super::{Sensor, SensorPort};
 use crate::{sensor_mode, Attribute, Device, Driver, Ev3Error, Ev3Result};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time to wait after a mode switch until the sensor reports valid values.
+const MODE_SWITCH_DELAY: Duration = Duration::from_millis(100);
+
+/// Interval in which the rotational speed is sampled during calibration.
+const CALIBRATION_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Maximal spread of the rotational speed samples (in deg/s) while the sensor is considered stationary.
+const STATIONARY_RATE: i32 = 3;
 
 /// LEGO EV3 gyro sensor.
 #[derive(Debug, Clone, Device, Sensor)]
@@ -97,4 +108,199 @@ impl GyroSensor {
             }),
         }
     }
+
+    /// Resets the sensor and measures its bias while it is stationary.
+    ///
+    /// The sensor is reset by switching to `GYRO-CAL` and back, which also sets the angle to `0`.
+    /// Then the rotational speed is averaged for `duration`, the result is the bias in degrees per second.
+    /// Returns an error if the sensor was moved during the measurement.
+    /// Afterwards the sensor is in `GYRO-G&A` mode.
+    ///
+    /// ```no_run
+    /// use ev3dev_lang_rust::sensors::GyroSensor;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let gyro = GyroSensor::find()?;
+    ///
+    /// let bias = gyro.calibrate(Duration::from_secs(2))?;
+    /// println!("Bias: {bias} deg/s");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn calibrate(&self, duration: Duration) -> Ev3Result<f32> {
+        self.set_mode_gyro_cal()?;
+        thread::sleep(MODE_SWITCH_DELAY);
+        self.set_mode_gyro_rate()?;
+        thread::sleep(MODE_SWITCH_DELAY);
+
+        let mut samples = Vec::new();
+        let start = Instant::now();
+        while start.elapsed() < duration || samples.is_empty() {
+            samples.push(self.get_value0()?);
+            thread::sleep(CALIBRATION_INTERVAL);
+        }
+
+        self.set_mode_gyro_g_and_a()?;
+        thread::sleep(MODE_SWITCH_DELAY);
+
+        let min = samples.iter().min().copied().unwrap_or(0);
+        let max = samples.iter().max().copied().unwrap_or(0);
+        if max - min > STATIONARY_RATE {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Gyro sensor moved during calibration ({min} to {max} deg/s)"),
+            });
+        }
+
+        Ok(samples.iter().sum::<i32>() as f32 / samples.len() as f32)
+    }
+}
+
+/// Wraps an angle in degrees to the range `(-180, 180]`.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::sensors::wrap_angle;
+///
+/// assert_eq!(wrap_angle(190.0), -170.0);
+/// assert_eq!(wrap_angle(-180.0), 180.0);
+/// assert_eq!(wrap_angle(720.5), 0.5);
+/// ```
+pub fn wrap_angle(angle: f32) -> f32 {
+    let wrapped = angle.rem_euclid(360.0);
+    if wrapped > 180.0 {
+        wrapped - 360.0
+    } else {
+        wrapped
+    }
+}
+
+/// Gyro sensor wrapper that subtracts the measured bias from the angle.
+///
+/// The raw angle of the sensor drifts by the bias every second. The wrapper integrates
+/// the changes of the raw angle and removes the drift, so `update()` must be called regularly.
+/// With `set_adaptive(true)` the bias is re-estimated while the sensor is stationary.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sensors::{CompensatedGyro, GyroSensor};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut gyro = CompensatedGyro::calibrate(GyroSensor::find()?, Duration::from_secs(2))?;
+///
+/// loop {
+///     gyro.update()?;
+///     println!("Heading: {:.1}, turned: {:.1}", gyro.get_wrapped_angle(), gyro.get_angle());
+///     thread::sleep(Duration::from_millis(10));
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompensatedGyro {
+    sensor: GyroSensor,
+    bias: f32,
+    angle: f32,
+    rate: f32,
+    last_angle: i32,
+    last_update: Instant,
+    adaptive: bool,
+}
+
+impl CompensatedGyro {
+    /// Smoothing factor per second for the adaptive bias estimation.
+    const ADAPTION_RATE: f32 = 0.05;
+
+    /// Wrap a gyro sensor with a known `bias` in degrees per second. Switches the sensor to `GYRO-G&A` mode.
+    pub fn new(sensor: GyroSensor, bias: f32) -> Ev3Result<CompensatedGyro> {
+        if !sensor.is_mode_gyro_g_and_a()? {
+            sensor.set_mode_gyro_g_and_a()?;
+            thread::sleep(MODE_SWITCH_DELAY);
+        }
+        let last_angle = sensor.get_value0()?;
+
+        Ok(CompensatedGyro {
+            sensor,
+            bias,
+            angle: 0.0,
+            rate: 0.0,
+            last_angle,
+            last_update: Instant::now(),
+            adaptive: false,
+        })
+    }
+
+    /// Calibrate the gyro sensor with `GyroSensor::calibrate()` and wrap it with the measured bias.
+    pub fn calibrate(sensor: GyroSensor, duration: Duration) -> Ev3Result<CompensatedGyro> {
+        let bias = sensor.calibrate(duration)?;
+        CompensatedGyro::new(sensor, bias)
+    }
+
+    /// Returns the wrapped sensor.
+    pub fn get_sensor(&self) -> &GyroSensor {
+        &self.sensor
+    }
+
+    /// Returns the bias in degrees per second.
+    pub fn get_bias(&self) -> f32 {
+        self.bias
+    }
+
+    /// Sets the bias in degrees per second.
+    pub fn set_bias(&mut self, bias: f32) {
+        self.bias = bias;
+    }
+
+    /// Enables or disables the re-estimation of the bias while the sensor is stationary.
+    /// Slow rotations below 3 deg/s may be mistaken for drift.
+    pub fn set_adaptive(&mut self, adaptive: bool) {
+        self.adaptive = adaptive;
+    }
+
+    /// Reads the sensor and integrates the compensated angle. Returns the unwrapped angle.
+    pub fn update(&mut self) -> Ev3Result<f32> {
+        let angle = self.sensor.get_value0()?;
+        let rate = self.sensor.get_value1()?;
+
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+
+        let delta = (angle - self.last_angle) as f32;
+        self.last_angle = angle;
+
+        if self.adaptive && dt > 0.0 && (rate as f32 - self.bias).abs() < STATIONARY_RATE as f32 {
+            let alpha = (Self::ADAPTION_RATE * dt).min(1.0);
+            self.bias += alpha * (delta / dt - self.bias);
+        }
+
+        self.angle += delta - self.bias * dt;
+        self.rate = rate as f32 - self.bias;
+        Ok(self.angle)
+    }
+
+    /// Returns the compensated angle in degrees since the last reset, without wrap-around.
+    pub fn get_angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// Returns the compensated angle in degrees in the range `(-180, 180]`.
+    pub fn get_wrapped_angle(&self) -> f32 {
+        wrap_angle(self.angle)
+    }
+
+    /// Returns the compensated rotational speed of the last update in degrees per second.
+    pub fn get_rotational_speed(&self) -> f32 {
+        self.rate
+    }
+
+    /// Sets the current angle to `angle` degrees.
+    pub fn set_angle(&mut self, angle: f32) {
+        self.angle = angle;
+    }
+
+    /// Sets the current angle to `0`.
+    pub fn reset(&mut self) {
+        self.set_angle(0.0);
+    }
 }
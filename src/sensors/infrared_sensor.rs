@@ -1,9 +1,10 @@
 //! LEGO EV3 infrared sensor.
 
 use super::{Sensor, SensorPort};
+use crate::filter::Filter;
 use crate::{sensor_mode, Attribute, Device, Driver, Ev3Error, Ev3Result};
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
@@ -75,143 +76,411 @@ impl InfraredSensor {
     }
 }
 
+/// Button of the EV3 infrared remote control.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RemoteButton {
+    /// Red up button (top left).
+    RedUp,
+    /// Red down button (bottom left).
+    RedDown,
+    /// Blue up button (top right).
+    BlueUp,
+    /// Blue down button (bottom right).
+    BlueDown,
+    /// Beacon button (top center).
+    Beacon,
+}
+
+impl RemoteButton {
+    /// All buttons of the remote control.
+    pub const ALL: [RemoteButton; 5] = [
+        RemoteButton::RedUp,
+        RemoteButton::RedDown,
+        RemoteButton::BlueUp,
+        RemoteButton::BlueDown,
+        RemoteButton::Beacon,
+    ];
+
+    fn mask(self) -> u8 {
+        match self {
+            RemoteButton::RedUp => 0x01,
+            RemoteButton::RedDown => 0x02,
+            RemoteButton::BlueUp => 0x04,
+            RemoteButton::BlueDown => 0x08,
+            RemoteButton::Beacon => 0x10,
+        }
+    }
+
+    fn from_mask(mask: u8) -> Vec<RemoteButton> {
+        RemoteButton::ALL
+            .iter()
+            .copied()
+            .filter(|button| mask & button.mask() != 0)
+            .collect()
+    }
+
+    /// Bit mask of the buttons for an `IR-REMOTE` value of a single channel.
+    fn remote_mask(value: i32) -> u8 {
+        use RemoteButton::*;
+        match value {
+            1 => RedUp.mask(),
+            2 => RedDown.mask(),
+            3 => BlueUp.mask(),
+            4 => BlueDown.mask(),
+            5 => RedUp.mask() | BlueUp.mask(),
+            6 => RedUp.mask() | BlueDown.mask(),
+            7 => RedDown.mask() | BlueUp.mask(),
+            8 => RedDown.mask() | BlueDown.mask(),
+            9 => Beacon.mask(),
+            10 => RedUp.mask() | RedDown.mask(),
+            11 => BlueUp.mask() | BlueDown.mask(),
+            _ => 0,
+        }
+    }
+
+    /// Decodes an `IR-REMOTE` value (0 - 11) of a single channel to the pressed buttons.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::RemoteButton;
+    ///
+    /// assert_eq!(RemoteButton::decode(6), vec![RemoteButton::RedUp, RemoteButton::BlueDown]);
+    /// assert_eq!(RemoteButton::decode(0), vec![]);
+    /// ```
+    pub fn decode(value: i32) -> Vec<RemoteButton> {
+        RemoteButton::from_mask(RemoteButton::remote_mask(value))
+    }
+
+    /// Bit masks of the buttons of all four channels for an `IR-REM-A` value.
+    fn alternate_masks(value: i32) -> [u8; 4] {
+        let mut masks = [0u8; 4];
+        for (channel, mask) in masks.iter_mut().enumerate() {
+            *mask = ((value >> (channel * 4)) & 0x0F) as u8;
+        }
+        masks
+    }
+
+    /// Decodes an `IR-REM-A` value to the pressed buttons of the channels 1 to 4.
+    ///
+    /// The value holds four bits per channel, starting with channel 1 in the lowest bits,
+    /// in the order red up, red down, blue up, blue down.
+    /// Unlike `IR-REMOTE` any combination of buttons can be reported, but the beacon button is not.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sensors::RemoteButton;
+    ///
+    /// let channels = RemoteButton::decode_alternate(0x0B0);
+    /// assert_eq!(channels[0], vec![]);
+    /// assert_eq!(
+    ///     channels[1],
+    ///     vec![RemoteButton::RedUp, RemoteButton::RedDown, RemoteButton::BlueDown]
+    /// );
+    /// ```
+    pub fn decode_alternate(value: i32) -> [Vec<RemoteButton>; 4] {
+        RemoteButton::alternate_masks(value).map(RemoteButton::from_mask)
+    }
+}
+
+/// Encoding of the remote control buttons.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemoteMode {
+    /// `IR-REMOTE` mode: one value per channel, at most two buttons or the beacon per channel.
+    Standard,
+    /// `IR-REM-A` mode: a single bit-encoded value, any button combination but no beacon.
+    Alternate,
+}
+
+/// Handlers are reference counted, so `process()` can call them without borrowing the helper.
+type RemoteChangeHandler = Rc<dyn Fn(u8, Vec<RemoteButton>)>;
+type RemoteButtonHandler = Rc<dyn Fn(bool)>;
+
 struct RemoteControlHelper {
-    last_buttons: i32,
-    pressed_buttons: HashSet<String>,
+    pressed_buttons: [u8; 4],
+    button_handlers: HashMap<(u8, RemoteButton), RemoteButtonHandler>,
+    change_handler: Option<RemoteChangeHandler>,
 }
 
 impl RemoteControlHelper {
     fn new() -> RemoteControlHelper {
         RemoteControlHelper {
-            last_buttons: 0,
-            pressed_buttons: HashSet::new(),
+            pressed_buttons: [0; 4],
+            button_handlers: HashMap::new(),
+            change_handler: None,
         }
     }
 
-    fn contains(&self, button: &str) -> bool {
-        self.pressed_buttons.contains(button)
+    fn contains(&self, channel: u8, button: RemoteButton) -> bool {
+        self.pressed_buttons[channel as usize] & button.mask() != 0
     }
 }
 
-/// Seeks EV3 Remote Controller in beacon mode.
+/// Reads the EV3 Remote Controller on all four channels.
+///
+/// Each call of `process()` reads one sample of all channels and calls the handlers
+/// of the buttons whose state changed. Channels are numbered 1 to 4 like on the remote.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sensors::{InfraredSensor, RemoteButton, RemoteControl};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut remote = RemoteControl::new(InfraredSensor::find()?, 1)?;
+///
+/// remote.set_handler(2, RemoteButton::Beacon, |is_pressed| {
+///     println!("Beacon on channel 2 pressed: {is_pressed}");
+/// });
+/// remote.set_change_handler(|channel, buttons| {
+///     println!("Channel {channel}: {:?}", buttons);
+/// });
+///
+/// loop {
+///     remote.process()?;
+///     println!("Is 'red up' on channel 1 pressed: {}", remote.is_red_up());
+///     thread::sleep(Duration::from_millis(100));
+/// }
+/// # }
+/// ```
 #[derive(Clone)]
 pub struct RemoteControl {
     sensor: InfraredSensor,
     channel: u8,
+    mode: RemoteMode,
     helper: Rc<RefCell<RemoteControlHelper>>,
 }
 
-// Manually implement Debug cause `buffer_cache` does not implement Debug.
+// Manually implement Debug cause the handlers do not implement Debug.
 impl fmt::Debug for RemoteControl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RemoteControl")
             .field("sensor", &self.sensor)
             .field("channel", &self.channel)
+            .field("mode", &self.mode)
             .finish()
     }
 }
 
 impl RemoteControl {
-    /// Wrap a InfraredSensor into a BeaconSeeker
+    /// Wrap a InfraredSensor into a RemoteControl in `IR-REMOTE` mode.
+    /// The `is_*` functions report the buttons of the given `channel`.
     pub fn new(sensor: InfraredSensor, channel: u8) -> Ev3Result<RemoteControl> {
-        sensor.set_mode_ir_remote()?;
+        RemoteControl::with_mode(sensor, channel, RemoteMode::Standard)
+    }
+
+    /// Wrap a InfraredSensor into a RemoteControl with the given button encoding.
+    /// The `is_*` functions report the buttons of the given `channel`.
+    pub fn with_mode(
+        sensor: InfraredSensor,
+        channel: u8,
+        mode: RemoteMode,
+    ) -> Ev3Result<RemoteControl> {
+        match mode {
+            RemoteMode::Standard => sensor.set_mode_ir_remote()?,
+            RemoteMode::Alternate => sensor.set_mode_ir_rem_a()?,
+        }
 
         Ok(RemoteControl {
             sensor,
-            channel: u8::max(1, u8::min(4, channel)) - 1,
+            channel: clamp_channel(channel),
+            mode,
             helper: Rc::new(RefCell::new(RemoteControlHelper::new())),
         })
     }
 
+    /// Returns the button encoding.
+    pub fn get_mode(&self) -> RemoteMode {
+        self.mode
+    }
+
     /// Checks if `red_up` button is pressed.
     pub fn is_red_up(&self) -> bool {
-        self.helper.borrow().contains("red_up")
+        self.helper
+            .borrow()
+            .contains(self.channel, RemoteButton::RedUp)
     }
 
     /// Checks if `red_down` button is pressed.
     pub fn is_red_down(&self) -> bool {
-        self.helper.borrow().contains("red_down")
+        self.helper
+            .borrow()
+            .contains(self.channel, RemoteButton::RedDown)
     }
 
     /// Checks if `blue_up` button is pressed.
     pub fn is_blue_up(&self) -> bool {
-        self.helper.borrow().contains("blue_up")
+        self.helper
+            .borrow()
+            .contains(self.channel, RemoteButton::BlueUp)
     }
 
     /// Checks if `blue_down` button is pressed.
     pub fn is_blue_down(&self) -> bool {
-        self.helper.borrow().contains("blue_down")
+        self.helper
+            .borrow()
+            .contains(self.channel, RemoteButton::BlueDown)
     }
 
     /// Checks if `beacon` button is pressed.
     pub fn is_beacon(&self) -> bool {
-        self.helper.borrow().contains("beacon")
+        self.helper
+            .borrow()
+            .contains(self.channel, RemoteButton::Beacon)
+    }
+
+    /// Checks if the `button` is pressed on the given `channel` (1 - 4).
+    pub fn is_pressed(&self, channel: u8, button: RemoteButton) -> bool {
+        self.helper
+            .borrow()
+            .contains(clamp_channel(channel), button)
+    }
+
+    /// Returns the pressed buttons on the given `channel` (1 - 4).
+    pub fn get_pressed_buttons(&self, channel: u8) -> Vec<RemoteButton> {
+        RemoteButton::from_mask(
+            self.helper.borrow().pressed_buttons[clamp_channel(channel) as usize],
+        )
+    }
+
+    /// Set an event handler, that is called by `process()` if the pressed state of the `button`
+    /// on the given `channel` (1 - 4) changes.
+    pub fn set_handler(
+        &mut self,
+        channel: u8,
+        button: RemoteButton,
+        handler: impl Fn(bool) + 'static,
+    ) {
+        self.helper
+            .borrow_mut()
+            .button_handlers
+            .insert((clamp_channel(channel), button), Rc::new(handler));
+    }
+
+    /// Removes the event handler of the `button` on the given `channel` (1 - 4).
+    pub fn remove_handler(&mut self, channel: u8, button: RemoteButton) {
+        self.helper
+            .borrow_mut()
+            .button_handlers
+            .remove(&(clamp_channel(channel), button));
+    }
+
+    /// Set an event handler, that is called by `process()` if any button state of a channel changes.
+    /// Has the channel (1 - 4) and all pressed buttons of this channel as parameters.
+    pub fn set_change_handler(&mut self, handler: impl Fn(u8, Vec<RemoteButton>) + 'static) {
+        self.helper.borrow_mut().change_handler = Some(Rc::new(handler));
+    }
+
+    /// Removes the change event handler.
+    pub fn remove_change_handler(&mut self) {
+        self.helper.borrow_mut().change_handler = None;
+    }
+
+    /// Reads the pressed buttons of all channels from a single sample.
+    fn read_masks(&self) -> Ev3Result<[u8; 4]> {
+        match self.mode {
+            RemoteMode::Standard => {
+                let data = self.sensor.get_bin_data()?;
+                if data.len() < 4 {
+                    return Err(Ev3Error::InternalError {
+                        msg: format!("Expected 4 remote control values, got {}", data.len()),
+                    });
+                }
+                Ok([0, 1, 2, 3].map(|channel| RemoteButton::remote_mask(data[channel] as i32)))
+            }
+            RemoteMode::Alternate => Ok(RemoteButton::alternate_masks(self.sensor.get_value0()?)),
+        }
     }
 
     /// Check for currently pressed buttons. If the new state differs from the
     /// old state, call the appropriate button event handlers.
+    ///
+    /// The handlers are called after the state is updated, so they may query this remote control or its clones.
     pub fn process(&self) -> Ev3Result<()> {
-        let buttons = self.sensor.get_value(self.channel)?;
-
-        let mut helper = self.helper.borrow_mut();
+        let masks = self.read_masks()?;
 
-        if helper.last_buttons != buttons {
-            helper.last_buttons = buttons;
+        let (old_masks, button_handlers, change_handler) = {
+            let mut helper = self.helper.borrow_mut();
+            let old_masks = helper.pressed_buttons;
+            if old_masks == masks {
+                return Ok(());
+            }
+            helper.pressed_buttons = masks;
+            (
+                old_masks,
+                helper.button_handlers.clone(),
+                helper.change_handler.clone(),
+            )
+        };
 
-            helper.pressed_buttons.clear();
+        for channel in 0..4u8 {
+            let difference = old_masks[channel as usize] ^ masks[channel as usize];
+            if difference == 0 {
+                continue;
+            }
 
-            match buttons {
-                1 => {
-                    helper.pressed_buttons.insert("red_up".to_owned());
-                }
-                2 => {
-                    helper.pressed_buttons.insert("red_down".to_owned());
-                }
-                3 => {
-                    helper.pressed_buttons.insert("blue_up".to_owned());
-                }
-                4 => {
-                    helper.pressed_buttons.insert("blue_down".to_owned());
-                }
-                5 => {
-                    helper.pressed_buttons.insert("red_up".to_owned());
-                    helper.pressed_buttons.insert("blue_up".to_owned());
+            for button in RemoteButton::from_mask(difference) {
+                if let Some(handler) = button_handlers.get(&(channel, button)) {
+                    handler(masks[channel as usize] & button.mask() != 0);
                 }
-                6 => {
-                    helper.pressed_buttons.insert("red_up".to_owned());
-                    helper.pressed_buttons.insert("blue_down".to_owned());
-                }
-                7 => {
-                    helper.pressed_buttons.insert("red_down".to_owned());
-                    helper.pressed_buttons.insert("blue_up".to_owned());
-                }
-                8 => {
-                    helper.pressed_buttons.insert("red_down".to_owned());
-                    helper.pressed_buttons.insert("blue_down".to_owned());
-                }
-                9 => {
-                    helper.pressed_buttons.insert("beacon".to_owned());
-                }
-                10 => {
-                    helper.pressed_buttons.insert("red_up".to_owned());
-                    helper.pressed_buttons.insert("red_down".to_owned());
-                }
-                11 => {
-                    helper.pressed_buttons.insert("blue_up".to_owned());
-                    helper.pressed_buttons.insert("blue_down".to_owned());
-                }
-                _ => {}
+            }
+
+            if let Some(ref handler) = change_handler {
+                handler(
+                    channel + 1,
+                    RemoteButton::from_mask(masks[channel as usize]),
+                );
             }
         }
+
         Ok(())
     }
 }
 
+/// Converts a channel number 1 - 4 to an index 0 - 3.
+fn clamp_channel(channel: u8) -> u8 {
+    u8::max(1, u8::min(4, channel)) - 1
+}
+
+/// Heading and distance to a beacon.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BeaconReading {
+    /// Heading to the beacon in the range -25 to 25, negative values are left.
+    pub heading: f32,
+    /// Distance to the beacon in the range 0 to 100.
+    pub distance: f32,
+}
+
+/// Distance value of the `IR-SEEK` mode if no beacon is detected.
+const BEACON_NOT_DETECTED: i32 = -128;
+
 /// Seeks EV3 Remote Controller in beacon mode.
+///
+/// `get_beacons()` reads heading and distance of all four channels from one sample.
+/// An optional filter smooths the readings of each channel separately.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::filter::Filter;
+/// use ev3dev_lang_rust::sensors::{BeaconSeeker, InfraredSensor};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let seeker = BeaconSeeker::new(InfraredSensor::find()?, 1)?;
+/// seeker.set_filter(Some(Filter::median(5)));
+///
+/// loop {
+///     for (index, beacon) in seeker.get_beacons()?.iter().enumerate() {
+///         match beacon {
+///             Some(reading) => println!("Beacon {}: {:?}", index + 1, reading),
+///             None => println!("Beacon {}: not detected", index + 1),
+///         }
+///     }
+/// }
+/// # }
+/// ```
 #[derive(Debug, Clone)]
 pub struct BeaconSeeker {
     sensor: InfraredSensor,
     channel: u8,
+    filters: RefCell<Option<Vec<Filter>>>,
 }
 
 impl BeaconSeeker {
@@ -221,7 +490,8 @@ impl BeaconSeeker {
 
         Ok(BeaconSeeker {
             sensor,
-            channel: u8::max(1, u8::min(4, channel)) - 1,
+            channel: clamp_channel(channel),
+            filters: RefCell::new(None),
         })
     }
 
@@ -244,4 +514,64 @@ impl BeaconSeeker {
             self.sensor.get_value(self.channel * 2 + 1)?,
         ))
     }
+
+    /// Sets a filter for heading and distance of all channels, `None` disables filtering.
+    /// Each value is filtered by its own copy of the given filter.
+    pub fn set_filter(&self, filter: Option<Filter>) {
+        *self.filters.borrow_mut() = filter.map(|filter| vec![filter; 8]);
+    }
+
+    /// Returns the raw heading and distance of all four channels from one `bin_data` sample.
+    /// The distance is -128 if the beacon is not detected.
+    pub fn get_raw_beacons(&self) -> Ev3Result<[(i32, i32); 4]> {
+        let data = self.sensor.get_bin_data()?;
+        if data.len() < 8 {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Expected 8 beacon values, got {}", data.len()),
+            });
+        }
+
+        // The `IR-SEEK` values are signed bytes.
+        let value = |index: usize| data[index] as i8 as i32;
+        Ok([0, 1, 2, 3].map(|channel| (value(channel * 2), value(channel * 2 + 1))))
+    }
+
+    /// Returns heading and distance of all four channels from one sample, `None` if the beacon is not detected.
+    ///
+    /// If a filter is set, the readings are filtered. The filter of a channel is reset when its beacon is lost.
+    pub fn get_beacons(&self) -> Ev3Result<[Option<BeaconReading>; 4]> {
+        let raw = self.get_raw_beacons()?;
+        let mut filters = self.filters.borrow_mut();
+
+        let mut beacons = [None; 4];
+        for (channel, (heading, distance)) in raw.iter().enumerate() {
+            let filters = filters
+                .as_mut()
+                .map(|filters| &mut filters[channel * 2..channel * 2 + 2]);
+
+            if *distance == BEACON_NOT_DETECTED {
+                if let Some(filters) = filters {
+                    filters.iter_mut().for_each(Filter::reset);
+                }
+                continue;
+            }
+
+            let (heading, distance) = (*heading as f32, *distance as f32);
+            beacons[channel] = Some(match filters {
+                Some(filters) => BeaconReading {
+                    heading: filters[0].update(heading),
+                    distance: filters[1].update(distance),
+                },
+                None => BeaconReading { heading, distance },
+            });
+        }
+
+        Ok(beacons)
+    }
+
+    /// Returns heading and distance to the beacon on the given channel, `None` if the beacon is not detected.
+    /// Reads all channels, so the filters of the other channels are updated as well.
+    pub fn get_beacon(&self) -> Ev3Result<Option<BeaconReading>> {
+        Ok(self.get_beacons()?[self.channel as usize])
+    }
 }
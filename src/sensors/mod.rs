@@ -4,7 +4,10 @@ mod sensor;
 pub use self::sensor::Sensor;
 
 mod color_sensor;
-pub use self::color_sensor::ColorSensor;
+pub use self::color_sensor::{Color, ColorSensor};
+
+mod color_classifier;
+pub use self::color_classifier::{ColorCalibration, ColorClassifier, Hsv, Rgb};
 
 mod hi_technic_color_sensor;
 pub use self::hi_technic_color_sensor::HiTechnicColorSensor;
@@ -13,24 +16,30 @@ mod ir_seeker_sensor;
 pub use self::ir_seeker_sensor::IrSeekerSensor;
 
 mod compass_sensor;
-pub use self::compass_sensor::CompassSensor;
+pub use self::compass_sensor::{CompassReading, CompassSensor, HeadingFilter, SmoothedCompass};
 
 mod light_sensor;
 pub use self::light_sensor::LightSensor;
 
 mod gyro_sensor;
-pub use self::gyro_sensor::GyroSensor;
+pub use self::gyro_sensor::{wrap_angle, CompensatedGyro, GyroSensor};
 
 mod infrared_sensor;
+pub use self::infrared_sensor::BeaconReading;
 pub use self::infrared_sensor::BeaconSeeker;
 pub use self::infrared_sensor::InfraredSensor;
+pub use self::infrared_sensor::RemoteButton;
 pub use self::infrared_sensor::RemoteControl;
+pub use self::infrared_sensor::RemoteMode;
+
+mod sensor_stream;
+pub use self::sensor_stream::{SensorSample, SensorStream};
 
 mod touch_sensor;
 pub use self::touch_sensor::TouchSensor;
 
 mod ultrasonic_sensor;
-pub use self::ultrasonic_sensor::UltrasonicSensor;
+pub use self::ultrasonic_sensor::{DistanceFilter, FilteredUltrasonic, UltrasonicSensor};
 
 use crate::Ev3Result;
 use crate::LegoPort;
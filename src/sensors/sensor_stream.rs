@@ -0,0 +1,145 @@
+//! Timestamped sensor samples at the kernel polling rate.
+
+use super::Sensor;
+use crate::{wait, Attribute, Ev3Result};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Values of a sensor read at the same time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SensorSample {
+    /// Time since the stream was created.
+    pub timestamp: Duration,
+    /// The `value<N>` attributes of the current mode.
+    pub values: Vec<i32>,
+}
+
+/// Blocking stream of sensor samples.
+///
+/// The stream sets the kernel polling period `poll_ms` of the sensor and waits for changes of `value0`
+/// with `epoll`, see `wait::wait()`. If the value does not change, a sample is taken after each polling period anyway,
+/// unless `set_change_only(true)` is used. The number of values is taken from the mode at creation of the stream.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sensors::{GyroSensor, SensorStream};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let gyro = GyroSensor::find()?;
+/// gyro.set_mode_gyro_g_and_a()?;
+///
+/// let mut stream = SensorStream::new(&gyro, 10)?;
+/// stream.set_change_only(true);
+///
+/// for sample in stream.take(100) {
+///     let sample = sample?;
+///     println!("{:?}: angle {}, rate {}", sample.timestamp, sample.values[0], sample.values[1]);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SensorStream {
+    values: Vec<Attribute>,
+    interval: Duration,
+    decimation: usize,
+    change_only: bool,
+    start: Instant,
+    counter: usize,
+    last: Option<Vec<i32>>,
+}
+
+impl SensorStream {
+    /// Create a stream for the current mode of the sensor and set its polling period to `poll_ms` milliseconds.
+    ///
+    /// If the sensor does not support changing `poll_ms`, the stream samples with this period nevertheless.
+    pub fn new<S: Sensor>(sensor: &S, poll_ms: u32) -> Ev3Result<SensorStream> {
+        // Sensors without kernel polling (e.g. some I2C sensors) reject the value, this is not fatal.
+        let _ = sensor.set_poll_ms(poll_ms as i32);
+
+        let num_values = sensor.get_num_values()?.clamp(1, 8);
+        let values = (0..num_values)
+            .map(|index| sensor.get_attribute(&format!("value{index}")))
+            .collect();
+
+        Ok(SensorStream {
+            values,
+            interval: Duration::from_millis(poll_ms.max(1) as u64),
+            decimation: 1,
+            change_only: false,
+            start: Instant::now(),
+            counter: 0,
+            last: None,
+        })
+    }
+
+    /// Only yield every `decimation`-th sample. `1` yields all samples.
+    pub fn set_decimation(&mut self, decimation: usize) {
+        self.decimation = decimation.max(1);
+    }
+
+    /// Only yield samples whose values differ from the previously yielded sample.
+    pub fn set_change_only(&mut self, change_only: bool) {
+        self.change_only = change_only;
+    }
+
+    /// Read all values of the current mode.
+    fn read(&self) -> Ev3Result<Vec<i32>> {
+        self.values.iter().map(|value| value.get()).collect()
+    }
+
+    /// Wait for the next sample that passes decimation and change filter.
+    pub fn next_sample(&mut self) -> Ev3Result<SensorSample> {
+        let fd = self.values[0].get_raw_fd();
+
+        loop {
+            if let Some(last) = &self.last {
+                // Wake up on a change notification of the attribute or after one polling period.
+                wait::wait(
+                    fd,
+                    || self.read().map(|values| values != *last).unwrap_or(true),
+                    Some(self.interval),
+                );
+            }
+
+            let timestamp = self.start.elapsed();
+            let values = self.read()?;
+
+            self.counter += 1;
+            if !self.counter.is_multiple_of(self.decimation) {
+                continue;
+            }
+            if self.change_only && self.last.as_ref() == Some(&values) {
+                continue;
+            }
+
+            self.last = Some(values.clone());
+            return Ok(SensorSample { timestamp, values });
+        }
+    }
+
+    /// Move the stream to a background thread and receive its samples through a channel.
+    ///
+    /// The thread ends after a read error or when the receiver is dropped.
+    pub fn into_channel(mut self) -> Receiver<Ev3Result<SensorSample>> {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || loop {
+            let sample = self.next_sample();
+            let failed = sample.is_err();
+            if sender.send(sample).is_err() || failed {
+                break;
+            }
+        });
+
+        receiver
+    }
+}
+
+impl Iterator for SensorStream {
+    type Item = Ev3Result<SensorSample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_sample())
+    }
+}
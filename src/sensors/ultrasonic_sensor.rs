@@ -1,8 +1,17 @@
 //! LEGO EV3 ultrasonic sensor
 
 use super::{Sensor, SensorPort};
+use crate::filter::MedianFilter;
 use crate::{sensor_mode, Attribute, Device, Driver, Ev3Error, Ev3Result};
 use std::cell::Cell;
+use std::thread;
+use std::time::Duration;
+
+/// Time the sensor needs for a single measurement or to settle after a mode switch.
+const MEASUREMENT_DELAY: Duration = Duration::from_millis(100);
+
+/// Distance in centimeters reported if no echo is received.
+const MAX_DISTANCE_CENTIMETERS: f32 = 255.0;
 
 /// LEGO EV3 ultrasonic sensor.
 #[derive(Debug, Clone, Device, Sensor)]
@@ -115,4 +124,212 @@ impl UltrasonicSensor {
 
         Ok((self.get_value0()? as f32) * scale)
     }
+
+    /// Triggers a single measurement in `US-SI-CM` mode and returns the distance in centimeters.
+    ///
+    /// Each write of the mode triggers a new measurement, even if the sensor is already in this mode.
+    /// Between the measurements the sensor is silent, so it does not interfere with other ultrasonic sensors.
+    pub fn ping_centimeters(&self) -> Ev3Result<f32> {
+        self.set_mode_us_si_cm()?;
+        thread::sleep(MEASUREMENT_DELAY);
+        self.get_distance_centimeters()
+    }
+
+    /// Triggers a single measurement in `US-SI-IN` mode and returns the distance in inches.
+    /// See `ping_centimeters()`.
+    pub fn ping_inches(&self) -> Ev3Result<f32> {
+        self.set_mode_us_si_in()?;
+        thread::sleep(MEASUREMENT_DELAY);
+        self.get_distance_inches()
+    }
+
+    /// Checks in `US-LISTEN` mode if another ultrasonic sensor is sending nearby.
+    ///
+    /// Switches to `US-LISTEN` mode if necessary and waits for the sensor to settle.
+    pub fn listen(&self) -> Ev3Result<bool> {
+        if !self.is_mode_us_listen()? {
+            self.set_mode_us_listen()?;
+            thread::sleep(MEASUREMENT_DELAY);
+        }
+        Ok(self.get_value0()? == 1)
+    }
+}
+
+/// Ultrasonic sensor wrapper with median filtering and outlier rejection.
+///
+/// Crosstalk of other ultrasonic sensors and missed echoes produce single readings that are far off.
+/// A reading that differs from the filtered distance by more than the maximal jump is rejected,
+/// unless the following readings confirm it. Readings without echo (255 cm) report `None`
+/// once they are confirmed.
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sensors::{FilteredUltrasonic, UltrasonicSensor};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let sensor = UltrasonicSensor::find()?;
+/// if sensor.listen()? {
+///     println!("Another ultrasonic sensor is active nearby");
+/// }
+///
+/// let mut filtered = FilteredUltrasonic::new(sensor, 5)?;
+/// loop {
+///     match filtered.update()? {
+///         Some(distance) => println!("Distance: {distance:.1} cm"),
+///         None => println!("Nothing in range"),
+///     }
+///     thread::sleep(Duration::from_millis(50));
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilteredUltrasonic {
+    sensor: UltrasonicSensor,
+    filter: DistanceFilter,
+}
+
+impl FilteredUltrasonic {
+    /// Wrap an ultrasonic sensor with a median filter over `window` readings.
+    /// Switches the sensor to `US-DIST-CM` mode.
+    pub fn new(sensor: UltrasonicSensor, window: usize) -> Ev3Result<FilteredUltrasonic> {
+        if !sensor.is_mode_us_dist_cm()? {
+            sensor.set_mode_us_dist_cm()?;
+            thread::sleep(MEASUREMENT_DELAY);
+        }
+
+        Ok(FilteredUltrasonic {
+            sensor,
+            filter: DistanceFilter::new(window),
+        })
+    }
+
+    /// Returns the wrapped sensor.
+    pub fn get_sensor(&self) -> &UltrasonicSensor {
+        &self.sensor
+    }
+
+    /// Sets the maximal change in centimeters between a reading and the filtered distance. Defaults to 30 cm.
+    pub fn set_max_jump(&mut self, max_jump: f32) {
+        self.filter.set_max_jump(max_jump);
+    }
+
+    /// Sets the number of consecutive outliers that are rejected before they are accepted as real change.
+    /// Defaults to 2.
+    pub fn set_max_rejections(&mut self, max_rejections: u32) {
+        self.filter.set_max_rejections(max_rejections);
+    }
+
+    /// Reads the sensor and returns the filtered distance in centimeters or `None` if nothing is in range.
+    pub fn update(&mut self) -> Ev3Result<Option<f32>> {
+        let reading = self.sensor.get_distance_centimeters()?;
+        Ok(self.filter.update(reading))
+    }
+
+    /// Returns the filtered distance of the last update in centimeters or `None` if nothing is in range.
+    pub fn get_distance(&self) -> Option<f32> {
+        self.filter.get_distance()
+    }
+}
+
+/// Median filter with outlier rejection for ultrasonic distances, independent of the sensor.
+///
+/// A reading that differs by more than `max_jump` from the filtered distance, or that appears or
+/// disappears out of range, is rejected. After more than `max_rejections` consecutive outliers
+/// the change is accepted and the filter starts over.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::sensors::DistanceFilter;
+///
+/// let mut filter = DistanceFilter::new(3);
+/// assert_eq!(filter.update(50.0), Some(50.0));
+/// assert_eq!(filter.update(52.0), Some(51.0));
+///
+/// // A single echo from the floor is rejected.
+/// assert_eq!(filter.update(10.0), Some(51.0));
+/// assert_eq!(filter.update(51.0), Some(51.0));
+///
+/// // An obstacle that stays is accepted after two rejections.
+/// assert_eq!(filter.update(20.0), Some(51.0));
+/// assert_eq!(filter.update(20.0), Some(51.0));
+/// assert_eq!(filter.update(20.0), Some(20.0));
+///
+/// // Out of range readings are handled the same way.
+/// assert_eq!(filter.update(255.0), Some(20.0));
+/// assert_eq!(filter.update(255.0), Some(20.0));
+/// assert_eq!(filter.update(255.0), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DistanceFilter {
+    filter: MedianFilter,
+    max_jump: f32,
+    max_rejections: u32,
+    rejections: u32,
+    /// Last accepted distance, `None` before the first update.
+    distance: Option<Option<f32>>,
+}
+
+impl DistanceFilter {
+    /// Create a filter with a median over `window` readings.
+    pub fn new(window: usize) -> DistanceFilter {
+        DistanceFilter {
+            filter: MedianFilter::new(window),
+            max_jump: 30.0,
+            max_rejections: 2,
+            rejections: 0,
+            distance: None,
+        }
+    }
+
+    /// Sets the maximal change in centimeters between a reading and the filtered distance. Defaults to 30 cm.
+    pub fn set_max_jump(&mut self, max_jump: f32) {
+        self.max_jump = max_jump;
+    }
+
+    /// Sets the number of consecutive outliers that are rejected before they are accepted as real change.
+    /// Defaults to 2.
+    pub fn set_max_rejections(&mut self, max_rejections: u32) {
+        self.max_rejections = max_rejections;
+    }
+
+    /// Adds a reading in centimeters and returns the filtered distance or `None` if nothing is in range.
+    /// Readings of 255 cm and more are out of range.
+    pub fn update(&mut self, reading: f32) -> Option<f32> {
+        let reading = if reading >= MAX_DISTANCE_CENTIMETERS {
+            None
+        } else {
+            Some(reading)
+        };
+
+        let is_outlier = match (self.distance, reading) {
+            (None, _) => false,
+            (Some(Some(distance)), Some(reading)) => (reading - distance).abs() > self.max_jump,
+            (Some(distance), reading) => distance.is_some() != reading.is_some(),
+        };
+
+        if is_outlier {
+            self.rejections += 1;
+            if self.rejections <= self.max_rejections {
+                return self.get_distance();
+            }
+            self.filter.reset();
+        }
+        self.rejections = 0;
+
+        let distance = match reading {
+            Some(reading) => Some(self.filter.update(reading)),
+            None => {
+                self.filter.reset();
+                None
+            }
+        };
+        self.distance = Some(distance);
+        distance
+    }
+
+    /// Returns the filtered distance of the last update in centimeters or `None` if nothing is in range.
+    pub fn get_distance(&self) -> Option<f32> {
+        self.distance.flatten()
+    }
 }
@@ -0,0 +1,186 @@
+//! Handles of sounds playing in the background.
+
+use crate::{Ev3Error, Ev3Result};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Interval to check if a sound was stopped or finished.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Handle of a sound playing in the background, returned by the functions of the `sound` module.
+///
+/// The sound is played either by system processes (`beep`, `aplay`, `espeak`) or by a background thread
+/// that uses the sound devices directly. Dropping the handle does not stop the sound.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mut handle = sound::speak("This is a very long sentence that nobody wants to hear")?;
+///
+/// if !handle.wait_timeout(Duration::from_secs(2))? {
+///     // Still speaking after two seconds.
+///     handle.stop()?;
+/// }
+/// assert!(!handle.is_playing());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SoundHandle {
+    inner: Inner,
+    stopped: bool,
+}
+
+#[derive(Debug)]
+enum Inner {
+    /// Processes with their program name, e.g. `espeak` piped to `aplay`.
+    Processes(Vec<(String, Child)>),
+    Thread {
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<Ev3Result<()>>>,
+    },
+}
+
+impl SoundHandle {
+    /// Create a handle of a spawned process that plays a sound.
+    pub fn from_child(child: Child) -> Self {
+        SoundHandle::from_processes(vec![("sound process".to_owned(), child)])
+    }
+
+    /// Create a handle of processes named by their program.
+    pub(super) fn from_processes(processes: Vec<(String, Child)>) -> Self {
+        SoundHandle {
+            inner: Inner::Processes(processes),
+            stopped: false,
+        }
+    }
+
+    /// Runs `play` in a background thread. It has to return soon after the flag is set.
+    pub(super) fn spawn<F>(name: &str, play: F) -> Ev3Result<Self>
+    where
+        F: FnOnce(&AtomicBool) -> Ev3Result<()> + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || play(&thread_stop))?;
+
+        Ok(SoundHandle {
+            inner: Inner::Thread {
+                stop,
+                thread: Some(thread),
+            },
+            stopped: false,
+        })
+    }
+
+    /// Stops the sound and blocks until it is stopped.
+    pub fn stop(&mut self) -> Ev3Result<()> {
+        self.stopped = true;
+        match &mut self.inner {
+            Inner::Processes(processes) => {
+                for (_, child) in processes.iter_mut() {
+                    if let Ok(None) = child.try_wait() {
+                        child.kill()?;
+                    }
+                }
+            }
+            Inner::Thread { stop, .. } => stop.store(true, Ordering::Relaxed),
+        }
+        self.wait()
+    }
+
+    /// Checks if the sound is still playing.
+    pub fn is_playing(&mut self) -> bool {
+        match &mut self.inner {
+            Inner::Processes(processes) => processes
+                .iter_mut()
+                .any(|(_, child)| matches!(child.try_wait(), Ok(None))),
+            Inner::Thread { thread, .. } => {
+                thread.as_ref().is_some_and(|thread| !thread.is_finished())
+            }
+        }
+    }
+
+    /// Blocks until the sound is finished.
+    /// Fails if a process exited with an error, unless the sound was stopped.
+    pub fn wait(&mut self) -> Ev3Result<()> {
+        match &mut self.inner {
+            Inner::Processes(processes) => {
+                for (program, child) in processes.iter_mut() {
+                    let status = child.wait()?;
+                    if !status.success() && !self.stopped {
+                        return Err(Ev3Error::InternalError {
+                            msg: format!("`{program}` failed with {status}"),
+                        });
+                    }
+                }
+                Ok(())
+            }
+            Inner::Thread { thread, .. } => match thread.take() {
+                Some(thread) => thread.join().map_err(|_| Ev3Error::InternalError {
+                    msg: "Sound thread panicked".to_owned(),
+                })?,
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Blocks until the sound is finished or the timeout has elapsed.
+    /// Returns `true` if the sound is finished.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Ev3Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if !self.is_playing() {
+                self.wait()?;
+                return Ok(true);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+}
+
+impl From<Child> for SoundHandle {
+    fn from(child: Child) -> Self {
+        SoundHandle::from_child(child)
+    }
+}
+
+/// Sleeps for the duration or until the flag is set. Returns `false` if it was stopped.
+pub(super) fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return false;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Waits for the sound to finish, or stops it when the flag is set. Returns `false` if it was stopped.
+pub(super) fn wait_unless_stopped(handle: &mut SoundHandle, stop: &AtomicBool) -> Ev3Result<bool> {
+    loop {
+        if handle.wait_timeout(POLL_INTERVAL)? {
+            return Ok(true);
+        }
+        if stop.load(Ordering::Relaxed) {
+            handle.stop()?;
+            return Ok(false);
+        }
+    }
+}
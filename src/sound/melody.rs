@@ -0,0 +1,531 @@
+//! Melodies from note names, RTTTL ringtones and MIDI files.
+
+use super::{play_tones, SoundHandle};
+use crate::{Ev3Error, Ev3Result};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Part of the note length that is played, the rest is silent so repeated notes can be distinguished.
+const ARTICULATION: f32 = 0.9;
+
+/// Default tempo of MIDI files in microseconds per quarter note (120 bpm).
+const MIDI_DEFAULT_TEMPO: u64 = 500_000;
+
+/// MIDI channel 10 contains percussion without pitch.
+const MIDI_PERCUSSION_CHANNEL: u8 = 9;
+
+/// Returns the frequency in Hz of a note name like `C4`, `A#5` or `Eb3` with equal temperament (`A4` = 440 Hz).
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::sound::note_frequency;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// assert_eq!(note_frequency("A4")?, 440.0);
+/// assert_eq!(note_frequency("A5")?, 880.0);
+/// assert_eq!(note_frequency("C#4")?.round(), 277.0);
+/// assert_eq!(note_frequency("Db4")?, note_frequency("C#4")?);
+/// assert!(note_frequency("X4").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn note_frequency(note: &str) -> Ev3Result<f32> {
+    let invalid = || Ev3Error::InternalError {
+        msg: format!("Invalid note '{note}'"),
+    };
+
+    let mut chars = note.chars();
+    let semitone: i32 = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => return Err(invalid()),
+    };
+
+    let rest = chars.as_str();
+    let (semitone, octave) = if let Some(octave) = rest.strip_prefix('#') {
+        (semitone + 1, octave)
+    } else if let Some(octave) = rest.strip_prefix('b') {
+        (semitone - 1, octave)
+    } else {
+        (semitone, rest)
+    };
+    let octave: i32 = octave.parse().map_err(|_| invalid())?;
+
+    Ok(midi_frequency((octave + 1) * 12 + semitone))
+}
+
+/// Frequency of a MIDI note number, 69 is `A4`.
+fn midi_frequency(note: i32) -> f32 {
+    440.0 * 2f32.powf((note - 69) as f32 / 12.0)
+}
+
+/// A sequence of tones that can be played in the background.
+///
+/// Tones are stored like the arguments of `sound::tone_sequence()`: frequency in Hz (0 for rests),
+/// duration and delay to the next tone in milliseconds.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::sound::Melody;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// // 120 quarter notes per minute, a quarter note lasts 500ms.
+/// let mut melody = Melody::new(120);
+/// melody.add_note("C4", 0.25)?;
+/// melody.add_rest(0.125);
+/// melody.add_note("A4", 0.5)?;
+///
+/// assert_eq!(melody.get_tones()[2], (440.0, 900, 100));
+/// assert_eq!(melody.get_duration().as_millis(), 1750);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ```no_run
+/// use ev3dev_lang_rust::sound::Melody;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let melody = Melody::parse_rtttl("Beep:d=8,o=5,b=160:c,e,g,4c6")?;
+/// let mut handle = melody.play()?;
+///
+/// std::thread::sleep(std::time::Duration::from_millis(500));
+/// handle.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Melody {
+    tempo: u32,
+    tones: Vec<(f32, i32, i32)>,
+}
+
+impl Melody {
+    /// Create an empty melody with the tempo in quarter notes per minute.
+    pub fn new(tempo: u32) -> Self {
+        Melody {
+            tempo: tempo.max(1),
+            tones: Vec::new(),
+        }
+    }
+
+    /// Returns the tempo in quarter notes per minute.
+    pub fn get_tempo(&self) -> u32 {
+        self.tempo
+    }
+
+    /// Sets the tempo in quarter notes per minute for notes added afterwards.
+    pub fn set_tempo(&mut self, tempo: u32) {
+        self.tempo = tempo.max(1);
+    }
+
+    /// Returns the tones as `(frequency, duration, delay)`.
+    pub fn get_tones(&self) -> &[(f32, i32, i32)] {
+        &self.tones
+    }
+
+    /// Returns the playing time of all tones and delays.
+    pub fn get_duration(&self) -> Duration {
+        let millis: i64 = self
+            .tones
+            .iter()
+            .map(|(_, duration, delay)| (*duration).max(0) as i64 + (*delay).max(0) as i64)
+            .sum();
+        Duration::from_millis(millis as u64)
+    }
+
+    /// Duration of a note in milliseconds.
+    /// `length` is the fraction of a whole note, e.g. `0.25` for a quarter note.
+    fn note_millis(&self, length: f32) -> i32 {
+        (length * 4.0 * 60_000.0 / self.tempo as f32).round() as i32
+    }
+
+    /// Appends a note like `C4` or `A#5`, see `note_frequency()`.
+    /// `length` is the fraction of a whole note, e.g. `0.25` for a quarter note or `0.375` for a dotted quarter note.
+    pub fn add_note(&mut self, note: &str, length: f32) -> Ev3Result<&mut Self> {
+        let frequency = note_frequency(note)?;
+        let millis = self.note_millis(length);
+        let duration = (millis as f32 * ARTICULATION).round() as i32;
+        self.tones.push((frequency, duration, millis - duration));
+        Ok(self)
+    }
+
+    /// Appends a rest. `length` is the fraction of a whole note.
+    pub fn add_rest(&mut self, length: f32) -> &mut Self {
+        let millis = self.note_millis(length);
+        self.tones.push((0.0, millis, 0));
+        self
+    }
+
+    /// Appends a tone with the frequency in Hz (0 for silence), duration and delay to the next tone in milliseconds.
+    pub fn add_tone(&mut self, frequency: f32, duration: i32, delay: i32) -> &mut Self {
+        self.tones.push((frequency, duration, delay));
+        self
+    }
+
+    /// Parses a RTTTL ringtone like `Name:d=4,o=5,b=120:8c,8d,e.,p,c6`.
+    ///
+    /// Notes consist of an optional length (1, 2, 4, ... for whole, half, quarter notes), the note `a` to `h`
+    /// or `p` for a pause, an optional `#`, an optional dot for dotted notes and an optional octave.
+    /// The defaults for length, octave and tempo (`b`, quarter notes per minute) are 4, 6 and 63.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sound::Melody;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let melody = Melody::parse_rtttl("Test:d=4,o=4,b=120:a,8p,2c#5.")?;
+    ///
+    /// assert_eq!(melody.get_tempo(), 120);
+    /// assert_eq!(melody.get_tones()[0], (440.0, 450, 50));
+    /// assert_eq!(melody.get_tones()[1], (0.0, 250, 0));
+    /// assert_eq!(melody.get_tones()[2].1 + melody.get_tones()[2].2, 1500);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_rtttl(rtttl: &str) -> Ev3Result<Melody> {
+        let invalid = |msg: String| Ev3Error::InternalError {
+            msg: format!("Invalid RTTTL: {msg}"),
+        };
+
+        let mut sections = rtttl.splitn(3, ':');
+        let (_name, settings, notes) = match (sections.next(), sections.next(), sections.next()) {
+            (Some(name), Some(settings), Some(notes)) => (name, settings, notes),
+            _ => return Err(invalid("expected 'name:settings:notes'".to_owned())),
+        };
+
+        let (mut default_length, mut default_octave, mut tempo) = (4u32, 6i32, 63u32);
+        for setting in settings.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = setting
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("setting '{setting}'")))?;
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| invalid(format!("setting '{setting}'")))?;
+            match key.trim().to_ascii_lowercase().as_str() {
+                "d" => default_length = value.max(1),
+                "o" => default_octave = value as i32,
+                "b" => tempo = value,
+                _ => {}
+            }
+        }
+
+        let mut melody = Melody::new(tempo);
+        for note in notes.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let lower = note.to_ascii_lowercase();
+            let digits = lower.chars().take_while(char::is_ascii_digit).count();
+            let length = if digits > 0 {
+                lower[..digits]
+                    .parse::<u32>()
+                    .map_err(|_| invalid(format!("note '{note}'")))?
+            } else {
+                default_length
+            };
+
+            let mut rest = lower[digits..].chars().peekable();
+            let semitone = match rest.next() {
+                Some('c') => Some(0),
+                Some('d') => Some(2),
+                Some('e') => Some(4),
+                Some('f') => Some(5),
+                Some('g') => Some(7),
+                Some('a') => Some(9),
+                Some('b') | Some('h') => Some(11),
+                Some('p') => None,
+                _ => return Err(invalid(format!("note '{note}'"))),
+            };
+
+            let mut sharp = false;
+            let mut dotted = false;
+            let mut octave = String::new();
+            for c in rest {
+                match c {
+                    '#' | '_' => sharp = true,
+                    '.' => dotted = true,
+                    c if c.is_ascii_digit() => octave.push(c),
+                    _ => return Err(invalid(format!("note '{note}'"))),
+                }
+            }
+            let octave = if octave.is_empty() {
+                default_octave
+            } else {
+                octave
+                    .parse()
+                    .map_err(|_| invalid(format!("note '{note}'")))?
+            };
+
+            let mut fraction = 1.0 / length.max(1) as f32;
+            if dotted {
+                fraction *= 1.5;
+            }
+            match semitone {
+                Some(semitone) => {
+                    let key = (octave + 1) * 12 + semitone + sharp as i32;
+                    let millis = melody.note_millis(fraction);
+                    let duration = (millis as f32 * ARTICULATION).round() as i32;
+                    melody
+                        .tones
+                        .push((midi_frequency(key), duration, millis - duration));
+                }
+                None => {
+                    melody.add_rest(fraction);
+                }
+            }
+        }
+
+        Ok(melody)
+    }
+
+    /// Parses a standard MIDI file (format 0 or 1).
+    ///
+    /// All tracks are merged into a single voice: a note sounds until it is released or the next note starts.
+    /// Percussion (channel 10) is ignored and tempo changes are applied.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sound::Melody;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let mut midi = Vec::new();
+    /// // Header: format 0, one track, 96 ticks per quarter note.
+    /// midi.extend_from_slice(b"MThd\0\0\0\x06\0\0\0\x01\0\x60");
+    /// let track = [
+    ///     0x00, 0x90, 69, 100, // A4 on
+    ///     0x60, 0x80, 69, 0,   // A4 off after a quarter note
+    ///     0x30, 0x90, 72, 100, // C5 on after an eighth rest
+    ///     0x60, 0x90, 72, 0,   // C5 off (note on with velocity 0)
+    ///     0x00, 0xff, 0x2f, 0, // end of track
+    /// ];
+    /// midi.extend_from_slice(b"MTrk\0\0\0\x14");
+    /// midi.extend_from_slice(&track);
+    ///
+    /// let melody = Melody::parse_midi(&midi)?;
+    /// assert_eq!(melody.get_tones()[0], (440.0, 500, 250));
+    /// assert_eq!(melody.get_tones()[1].1, 500);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_midi(bytes: &[u8]) -> Ev3Result<Melody> {
+        let mut reader = MidiReader::new(bytes);
+        if reader.take(4)? != b"MThd" {
+            return Err(midi_error("missing MThd header"));
+        }
+        let header_length = reader.u32()? as usize;
+        let header = reader.take(header_length)?;
+        if header.len() < 6 {
+            return Err(midi_error("header too short"));
+        }
+        let tracks = u16::from_be_bytes([header[2], header[3]]);
+        let division = u16::from_be_bytes([header[4], header[5]]);
+        if division & 0x8000 != 0 || division == 0 {
+            return Err(midi_error("SMPTE time division is not supported"));
+        }
+
+        let mut events = Vec::new();
+        for _ in 0..tracks {
+            let id = reader.take(4)?;
+            let length = reader.u32()? as usize;
+            let data = reader.take(length)?;
+            if id == b"MTrk" {
+                parse_midi_track(data, &mut events)?;
+            }
+        }
+        // Release notes before new notes start at the same tick.
+        events.sort_by_key(|(tick, event)| (*tick, matches!(event, MidiEvent::NoteOn(_))));
+
+        let mut melody = Melody::new(120);
+        let mut tempo = MIDI_DEFAULT_TEMPO;
+        let (mut last_tick, mut time) = (0u64, 0u64);
+        let mut last_end = 0u64;
+        let mut current: Option<(u8, u64)> = None;
+
+        for (tick, event) in events {
+            time += (tick - last_tick) * tempo / division as u64;
+            last_tick = tick;
+
+            match event {
+                MidiEvent::Tempo(value) => tempo = value,
+                MidiEvent::NoteOn(key) => {
+                    if let Some((key, start)) = current.take() {
+                        melody.add_midi_note(key, start, time, &mut last_end);
+                    }
+                    current = Some((key, time));
+                }
+                MidiEvent::NoteOff(key) => {
+                    if let Some((current_key, start)) = current {
+                        if current_key == key {
+                            melody.add_midi_note(key, start, time, &mut last_end);
+                            current = None;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some((key, start)) = current {
+            melody.add_midi_note(key, start, time, &mut last_end);
+        }
+
+        Ok(melody)
+    }
+
+    /// Reads and parses a standard MIDI file, see `parse_midi()`.
+    pub fn load_midi<P: AsRef<Path>>(path: P) -> Ev3Result<Melody> {
+        Melody::parse_midi(&fs::read(path)?)
+    }
+
+    /// Appends a MIDI note from `start` to `end` in microseconds. Silence since `last_end` becomes a delay.
+    fn add_midi_note(&mut self, key: u8, start: u64, end: u64, last_end: &mut u64) {
+        let millis = |micros: u64| ((micros + 500) / 1000) as i32;
+
+        let rest = millis(start.saturating_sub(*last_end));
+        if rest > 0 {
+            match self.tones.last_mut() {
+                Some((_, _, delay)) => *delay += rest,
+                None => self.tones.push((0.0, rest, 0)),
+            }
+        }
+        self.tones
+            .push((midi_frequency(key as i32), millis(end - start), 0));
+        *last_end = end;
+    }
+
+    /// Starts playing the melody in the background.
+    ///
+    /// Tones are played with the `ToneDevice` of the EV3, or with the `beep` command if it is not available.
+    pub fn play(&self) -> Ev3Result<SoundHandle> {
+        let tones = self.tones.clone();
+        SoundHandle::spawn("melody", move |stop| play_tones(&tones, stop))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum MidiEvent {
+    NoteOn(u8),
+    NoteOff(u8),
+    /// Microseconds per quarter note.
+    Tempo(u64),
+}
+
+fn midi_error(msg: &str) -> Ev3Error {
+    Ev3Error::InternalError {
+        msg: format!("Invalid MIDI file: {msg}"),
+    }
+}
+
+struct MidiReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> MidiReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        MidiReader { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, length: usize) -> Ev3Result<&'a [u8]> {
+        let end = self.position.saturating_add(length);
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| midi_error("unexpected end of file"))?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Ev3Result<u8> {
+        self.bytes
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| midi_error("unexpected end of file"))
+    }
+
+    fn u8(&mut self) -> Ev3Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Ev3Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Reads a variable length quantity with 7 bits per byte.
+    fn variable(&mut self) -> Ev3Result<u64> {
+        let mut value = 0u64;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(midi_error("variable length value too long"))
+    }
+}
+
+/// Collects the note and tempo events of a track with their absolute tick.
+fn parse_midi_track(data: &[u8], events: &mut Vec<(u64, MidiEvent)>) -> Ev3Result<()> {
+    let mut reader = MidiReader::new(data);
+    let mut tick = 0u64;
+    let mut running_status = None;
+
+    while !reader.is_empty() {
+        tick += reader.variable()?;
+
+        let status = if reader.peek()? & 0x80 != 0 {
+            reader.u8()?
+        } else {
+            running_status.ok_or_else(|| midi_error("data byte without status"))?
+        };
+
+        match status {
+            0xff => {
+                running_status = None;
+                let kind = reader.u8()?;
+                let length = reader.variable()? as usize;
+                let payload = reader.take(length)?;
+                match (kind, payload) {
+                    (0x2f, _) => break,
+                    (0x51, [a, b, c]) => {
+                        let tempo = u32::from_be_bytes([0, *a, *b, *c]) as u64;
+                        events.push((tick, MidiEvent::Tempo(tempo)));
+                    }
+                    _ => {}
+                }
+            }
+            0xf0 | 0xf7 => {
+                running_status = None;
+                let length = reader.variable()? as usize;
+                reader.take(length)?;
+            }
+            0x80..=0xef => {
+                running_status = Some(status);
+                let count = if matches!(status & 0xf0, 0xc0 | 0xd0) {
+                    1
+                } else {
+                    2
+                };
+                let data = reader.take(count)?;
+                if status & 0x0f == MIDI_PERCUSSION_CHANNEL {
+                    continue;
+                }
+                match status & 0xf0 {
+                    0x90 if data[1] > 0 => events.push((tick, MidiEvent::NoteOn(data[0]))),
+                    0x80 | 0x90 => events.push((tick, MidiEvent::NoteOff(data[0]))),
+                    _ => {}
+                }
+            }
+            _ => return Err(midi_error(&format!("unsupported event {status:#04x}"))),
+        }
+    }
+    Ok(())
+}
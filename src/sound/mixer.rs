@@ -0,0 +1,221 @@
+//! Volume controls of the ALSA mixer with raw `ioctl`s on the control device.
+
+use super::{ioc, ioctl, IOC_READ, IOC_WRITE};
+use crate::{Ev3Error, Ev3Result};
+use std::fs::{File, OpenOptions};
+use std::mem::size_of;
+
+/// Control device of the first sound card.
+pub const MIXER_PATH: &str = "/dev/snd/controlC0";
+
+/// Element types and interfaces of `include/uapi/sound/asound.h`.
+const SNDRV_CTL_ELEM_TYPE_INTEGER: i32 = 2;
+const SNDRV_CTL_ELEM_IFACE_MIXER: i32 = 2;
+
+const SNDRV_CTL_IOCTL_ELEM_LIST: u32 = ioc(IOC_READ | IOC_WRITE, b'U', 0x10, size_of::<ElemList>());
+const SNDRV_CTL_IOCTL_ELEM_INFO: u32 = ioc(IOC_READ | IOC_WRITE, b'U', 0x11, size_of::<ElemInfo>());
+const SNDRV_CTL_IOCTL_ELEM_READ: u32 =
+    ioc(IOC_READ | IOC_WRITE, b'U', 0x12, size_of::<ElemValue>());
+const SNDRV_CTL_IOCTL_ELEM_WRITE: u32 =
+    ioc(IOC_READ | IOC_WRITE, b'U', 0x13, size_of::<ElemValue>());
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct ElemId {
+    numid: u32,
+    iface: i32,
+    device: u32,
+    subdevice: u32,
+    name: [u8; 44],
+    index: u32,
+}
+
+impl ElemId {
+    fn get_name(&self) -> String {
+        let len = self.name.iter().position(|b| *b == 0).unwrap_or(44);
+        String::from_utf8_lossy(&self.name[..len]).into_owned()
+    }
+}
+
+#[repr(C)]
+struct ElemList {
+    offset: u32,
+    space: u32,
+    used: u32,
+    count: u32,
+    pids: *mut ElemId,
+    reserved: [u8; 50],
+}
+
+#[repr(C)]
+union InfoValue {
+    integer: [libc::c_long; 3],
+    integer64: [i64; 3],
+    reserved: [u8; 128],
+}
+
+#[repr(C)]
+struct ElemInfo {
+    id: ElemId,
+    type_: i32,
+    access: u32,
+    count: u32,
+    owner: libc::pid_t,
+    value: InfoValue,
+    reserved: [u8; 64],
+}
+
+#[repr(C)]
+union ValueData {
+    integer: [libc::c_long; 128],
+    integer64: [i64; 64],
+    bytes: [u8; 512],
+}
+
+#[repr(C)]
+struct ElemValue {
+    id: ElemId,
+    indirect: u32,
+    value: ValueData,
+    reserved: [u8; 128],
+}
+
+/// Integer volume element of the mixer.
+struct VolumeControl {
+    id: ElemId,
+    count: usize,
+    min: libc::c_long,
+    max: libc::c_long,
+}
+
+/// Volume controls of the sound card, like the simple controls of `amixer`.
+///
+/// A channel `<name>` controls the mixer element `<name> Playback Volume` or `<name> Volume`.
+/// Volumes are percentages of the element range, as shown by `amixer`.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::Mixer;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let mixer = Mixer::new()?;
+/// let channel = &mixer.get_channels()?[0];
+/// mixer.set_volume(channel, 50)?;
+/// assert_eq!(mixer.get_volume(channel)?, 50);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Mixer {
+    file: File,
+}
+
+impl Mixer {
+    /// Opens the control device `/dev/snd/controlC0`.
+    pub fn new() -> Ev3Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(MIXER_PATH)?;
+        Ok(Mixer { file })
+    }
+
+    /// Returns the names of all channels with a volume control.
+    pub fn get_channels(&self) -> Ev3Result<Vec<String>> {
+        let mut channels = Vec::new();
+        for id in self.get_elements()? {
+            let name = id.get_name();
+            let channel = name
+                .strip_suffix(" Playback Volume")
+                .or_else(|| name.strip_suffix(" Volume"));
+            if let Some(channel) = channel {
+                if !channels.iter().any(|c| c == channel) && self.get_control(&id).is_ok() {
+                    channels.push(channel.to_owned());
+                }
+            }
+        }
+        Ok(channels)
+    }
+
+    /// Returns the volume of the channel in percent [0-100].
+    pub fn get_volume(&self, channel: &str) -> Ev3Result<i32> {
+        let control = self.find_control(channel)?;
+        let value = self.read(&control)?;
+        let raw = unsafe { value.value.integer[0] };
+        let range = (control.max as f64 - control.min as f64).max(1.0);
+
+        Ok(((raw as f64 - control.min as f64) * 100.0 / range).round() as i32)
+    }
+
+    /// Sets the volume of all sides of the channel to the percentage [0-100].
+    pub fn set_volume(&self, channel: &str, volume: i32) -> Ev3Result<()> {
+        let control = self.find_control(channel)?;
+        let mut value = self.read(&control)?;
+        let range = control.max as f64 - control.min as f64;
+        let raw =
+            control.min + (volume.clamp(0, 100) as f64 * 0.01 * range).round() as libc::c_long;
+        for i in 0..control.count.min(128) {
+            unsafe { value.value.integer[i] = raw };
+        }
+        ioctl(&self.file, SNDRV_CTL_IOCTL_ELEM_WRITE, &mut value)?;
+        Ok(())
+    }
+
+    fn get_elements(&self) -> Ev3Result<Vec<ElemId>> {
+        let mut list: ElemList = unsafe { std::mem::zeroed() };
+        ioctl(&self.file, SNDRV_CTL_IOCTL_ELEM_LIST, &mut list)?;
+
+        let mut ids: Vec<ElemId> = vec![unsafe { std::mem::zeroed() }; list.count as usize];
+        list.space = list.count;
+        list.pids = ids.as_mut_ptr();
+        ioctl(&self.file, SNDRV_CTL_IOCTL_ELEM_LIST, &mut list)?;
+        ids.truncate(list.used as usize);
+
+        Ok(ids
+            .into_iter()
+            .filter(|id| id.iface == SNDRV_CTL_ELEM_IFACE_MIXER)
+            .collect())
+    }
+
+    fn get_control(&self, id: &ElemId) -> Ev3Result<VolumeControl> {
+        let mut info: ElemInfo = unsafe { std::mem::zeroed() };
+        info.id = *id;
+        ioctl(&self.file, SNDRV_CTL_IOCTL_ELEM_INFO, &mut info)?;
+        if info.type_ != SNDRV_CTL_ELEM_TYPE_INTEGER {
+            return Err(Ev3Error::InternalError {
+                msg: format!("Mixer element '{}' is not a volume", id.get_name()),
+            });
+        }
+
+        let [min, max, _] = unsafe { info.value.integer };
+        Ok(VolumeControl {
+            id: info.id,
+            count: info.count as usize,
+            min,
+            max,
+        })
+    }
+
+    fn find_control(&self, channel: &str) -> Ev3Result<VolumeControl> {
+        let names = [
+            format!("{channel} Playback Volume"),
+            format!("{channel} Volume"),
+        ];
+        for name in &names {
+            if let Some(id) = self
+                .get_elements()?
+                .into_iter()
+                .find(|id| id.get_name() == *name)
+            {
+                return self.get_control(&id);
+            }
+        }
+        Err(Ev3Error::InternalError {
+            msg: format!("Mixer has no volume control for channel '{channel}'"),
+        })
+    }
+
+    fn read(&self, control: &VolumeControl) -> Ev3Result<ElemValue> {
+        let mut value: ElemValue = unsafe { std::mem::zeroed() };
+        value.id = control.id;
+        ioctl(&self.file, SNDRV_CTL_IOCTL_ELEM_READ, &mut value)?;
+        Ok(value)
+    }
+}
@@ -0,0 +1,468 @@
+//! Sound-related functions. It can beep, play wav files, or convert text to
+//! speech.
+//!
+//! Note that `tone`, `tone_sequence`, `play` and `speak` return a `SoundHandle`.
+//! The methods are asynchronous (they return immediately after the sound was started,
+//! without waiting for its completion), but you can call wait() on the returned result or stop the sound.
+//! A `SoundQueue` plays sounds one after another without overlapping.
+//!
+//! Tones and wav files use the sound devices directly:
+//! tones are generated by the EV3 sound driver (`ToneDevice`)
+//! and wav files are written to the ALSA PCM device (`Pcm`).
+//! The volume functions use the ALSA mixer (`Mixer`).
+//! If a device is not available, e.g. on other platforms or while another program plays sound,
+//! these functions fall back to `beep`, `aplay` and `amixer`.
+//! `beep` and `beep_args` always run the `beep` command, `speak` runs `espeak`.
+//!
+//! `SpeakOptions` selects the voice, speed and pitch of `espeak` and renders speech to wav files.
+//!
+//! `Melody` plays note names, RTTTL ringtones and MIDI files in the background.
+//!
+//! The ALSA devices are driven with `ioctl`s instead of linking `libasound`,
+//! so no system libraries are needed for cross compiling.
+//!
+//! # Examples
+//! ```no_run
+//! # use ev3dev_lang_rust::Ev3Result;
+//! use ev3dev_lang_rust::sound::{self, PlayMode};
+//!
+//! # fn main() -> Ev3Result<()> {
+//! // Play "bark.wav", return immediately:
+//! let mut bark = sound::play_file("bark.wav", PlayMode::NoWait)?;
+//!
+//! // Introduce yourself, wait for completion:
+//! sound::speak("Hello, I am Robot")?.wait()?;
+//!
+//! // Stop barking, beep without spawning a process:
+//! bark.stop()?;
+//! sound::play_tone(440.0, 200)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::Ev3Result;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+mod handle;
+pub use self::handle::SoundHandle;
+use self::handle::{sleep_unless_stopped, wait_unless_stopped};
+
+mod melody;
+pub use self::melody::{note_frequency, Melody};
+
+mod mixer;
+pub use self::mixer::{Mixer, MIXER_PATH};
+
+mod pcm;
+pub use self::pcm::{Pcm, Wav, PCM_PATH};
+
+mod queue;
+pub use self::queue::{Sound, SoundQueue};
+
+mod speech;
+pub use self::speech::SpeakOptions;
+
+mod tone;
+pub use self::tone::{ToneDevice, TONE_DEVICE_PATH};
+
+/// Directions of `ioctl` requests, see `include/uapi/asm-generic/ioctl.h`.
+const IOC_NONE: u32 = 0;
+const IOC_WRITE: u32 = 1;
+const IOC_READ: u32 = 2;
+
+/// Encodes an `ioctl` request number like the `_IOC` macro.
+const fn ioc(direction: u32, kind: u8, number: u8, size: usize) -> u32 {
+    (direction << 30) | ((size as u32) << 16) | ((kind as u32) << 8) | number as u32
+}
+
+fn ioctl<T>(file: &File, request: u32, argument: *mut T) -> io::Result<()> {
+    if unsafe { libc::ioctl(file.as_raw_fd(), request as _, argument) } < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Call beep command.
+///
+/// # Example
+/// ```no_run
+/// # use ev3dev_lang_rust::Ev3Result;
+/// use ev3dev_lang_rust::sound;
+///
+/// # fn main() -> Ev3Result<()> {
+/// sound::beep()?.wait()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn beep() -> Ev3Result<SoundHandle> {
+    beep_args(Vec::<String>::new())
+}
+
+/// Call beep command with the provided arguments.
+///
+/// See `beep man page`_ and google `linux beep music`_ for inspiration.
+/// * `beep man page`: <https://linux.die.net/man/1/beep>
+/// * `linux beep music`: <https://www.google.com/search?q=linux+beep+music>
+///
+/// # Example
+/// ```no_run
+/// # use ev3dev_lang_rust::Ev3Result;
+/// use ev3dev_lang_rust::sound;
+///
+/// # fn main() -> Ev3Result<()> {
+/// sound::beep_args(&[""])?.wait()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn beep_args<I, S>(args: I) -> Ev3Result<SoundHandle>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let beep = Command::new("/usr/bin/beep")
+        .args(args)
+        .stdout(Stdio::null())
+        .spawn()?;
+    Ok(SoundHandle::from_processes(vec![("beep".to_owned(), beep)]))
+}
+
+/// Play a tone with the frequency in Hz for `duration` milliseconds in the background.
+///
+/// Uses the `ToneDevice` of the EV3 in a background thread and falls back to the `beep` command.
+///
+/// # Example
+/// ```no_run
+/// # use ev3dev_lang_rust::Ev3Result;
+/// use ev3dev_lang_rust::sound;
+///
+/// # fn main() -> Ev3Result<()> {
+/// sound::tone(466.0, 500)?.wait()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn tone(frequency: f32, duration: i32) -> Ev3Result<SoundHandle> {
+    tone_sequence(&[(frequency, duration, 0)])
+}
+
+/// Play tone sequence. The tone_sequence parameter is a list of tuples,
+/// where each tuple contains up to three numbers. The first number is
+/// frequency in Hz, the second is duration in milliseconds, and the third
+/// is delay in milliseconds between this and the next tone in the
+/// sequence.
+///
+/// Uses the `ToneDevice` of the EV3 in a background thread and falls back to the `beep` command.
+///
+/// # Example
+/// ```no_run
+/// # use ev3dev_lang_rust::Ev3Result;
+/// use ev3dev_lang_rust::sound;
+///
+/// # fn main() -> Ev3Result<()> {
+/// sound::tone_sequence(
+///     &[
+///         (392.00, 350, 100), (392.00, 350, 100), (392.00, 350, 100), (311.1, 250, 100),
+///         (466.20, 025, 100), (392.00, 350, 100), (311.10, 250, 100), (466.2, 025, 100),
+///         (392.00, 700, 100), (587.32, 350, 100), (587.32, 350, 100),
+///         (587.32, 350, 100), (622.26, 250, 100), (466.20, 025, 100),
+///         (369.99, 350, 100), (311.10, 250, 100), (466.20, 025, 100), (392.00, 700, 100),
+///         (784.00, 350, 100), (392.00, 250, 100), (392.00, 025, 100), (784.00, 350, 100),
+///         (739.98, 250, 100), (698.46, 025, 100), (659.26, 025, 100),
+///         (622.26, 025, 100), (659.26, 050, 400), (415.30, 025, 200), (554.36, 350, 100),
+///         (523.25, 250, 100), (493.88, 025, 100), (466.16, 025, 100), (440.00, 025, 100),
+///         (466.16, 050, 400), (311.13, 025, 200), (369.99, 350, 100),
+///         (311.13, 250, 100), (392.00, 025, 100), (466.16, 350, 100), (392.00, 250, 100),
+///         (466.16, 025, 100), (587.32, 700, 100), (784.00, 350, 100), (392.00, 250, 100),
+///         (392.00, 025, 100), (784.00, 350, 100), (739.98, 250, 100), (698.46, 025, 100),
+///         (659.26, 025, 100), (622.26, 025, 100), (659.26, 050, 400), (415.30, 025, 200),
+///         (554.36, 350, 100), (523.25, 250, 100), (493.88, 025, 100),
+///         (466.16, 025, 100), (440.00, 025, 100), (466.16, 050, 400), (311.13, 025, 200),
+///         (392.00, 350, 100), (311.13, 250, 100), (466.16, 025, 100),
+///         (392.00, 300, 150), (311.13, 250, 100), (466.16, 025, 100), (392.00, 700, 0)
+///     ]
+/// )?.wait()?;
+/// # Ok(())
+/// # }
+pub fn tone_sequence(sequence: &[(f32, i32, i32)]) -> Ev3Result<SoundHandle> {
+    let tones = sequence.to_vec();
+    SoundHandle::spawn("tone_sequence", move |stop| play_tones(&tones, stop))
+}
+
+/// Plays the tones with the `ToneDevice` or, if it is not available, with `beep`. Returns early if stopped.
+fn play_tones(tones: &[(f32, i32, i32)], stop: &AtomicBool) -> Ev3Result<()> {
+    let device = ToneDevice::new().ok();
+    let millis = |value: i32| Duration::from_millis(value.max(0) as u64);
+
+    for &(frequency, duration, delay) in tones {
+        let finished = match &device {
+            Some(device) => {
+                device.start(frequency)?;
+                let finished = sleep_unless_stopped(millis(duration), stop);
+                device.stop()?;
+                finished
+            }
+            None if frequency > 0.0 => {
+                wait_unless_stopped(&mut beep_tone(frequency, duration)?, stop)?
+            }
+            None => sleep_unless_stopped(millis(duration), stop),
+        };
+
+        if !finished || !sleep_unless_stopped(millis(delay), stop) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Play a single tone with the `beep` command.
+fn beep_tone(frequency: f32, duration: i32) -> Ev3Result<SoundHandle> {
+    beep_args(vec![format!("-f {frequency}"), format!("-l {duration}")])
+}
+
+/// Play a tone sequence with a single `beep` command.
+fn beep_tone_sequence(sequence: &[(f32, i32, i32)]) -> Ev3Result<SoundHandle> {
+    let tones: Vec<String> = sequence
+        .iter()
+        .map(|(frequency, duration, delay)| {
+            vec![
+                format!("-f {frequency}"),
+                format!("-l {duration}"),
+                format!("-D {delay}"),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>()
+        .join(&["-n".to_owned()][..]);
+
+    beep_args(tones)
+}
+
+/// Play a tone with the frequency in Hz for `duration` milliseconds and block until it is finished.
+///
+/// Uses the `ToneDevice` of the EV3 and falls back to the `beep` command.
+pub fn play_tone(frequency: f32, duration: i32) -> Ev3Result<()> {
+    match ToneDevice::new() {
+        Ok(device) => device.tone(frequency, Duration::from_millis(duration.max(0) as u64)),
+        Err(_) => beep_tone(frequency, duration)?.wait(),
+    }
+}
+
+/// Play a tone sequence like `tone_sequence()` and block until it is finished.
+///
+/// Uses the `ToneDevice` of the EV3 and falls back to the `beep` command.
+pub fn play_tone_sequence(sequence: &[(f32, i32, i32)]) -> Ev3Result<()> {
+    match ToneDevice::new() {
+        Ok(device) => {
+            for (frequency, duration, delay) in sequence {
+                device.tone(*frequency, Duration::from_millis(*duration.max(&0) as u64))?;
+                std::thread::sleep(Duration::from_millis(*delay.max(&0) as u64));
+            }
+            Ok(())
+        }
+        Err(_) => beep_tone_sequence(sequence)?.wait(),
+    }
+}
+
+/// How `play_file()` plays a wav file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PlayMode {
+    /// Block until the file is played.
+    #[default]
+    WaitForComplete,
+    /// Return immediately and play the file in the background.
+    NoWait,
+    /// Return immediately and repeat the file in the background until the sound is stopped.
+    Loop,
+}
+
+/// Play a wav file, see `PlayMode`. The returned handle can stop the sound.
+///
+/// The samples are written to the ALSA PCM device `/dev/snd/pcmC0D0p`. If the device is busy
+/// or does not support the format of the file, the file is played with `aplay` instead.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::{self, PlayMode};
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// sound::play_file("start.wav", PlayMode::WaitForComplete)?;
+///
+/// let mut music = sound::play_file("music.wav", PlayMode::Loop)?;
+/// std::thread::sleep(Duration::from_secs(10));
+/// music.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn play_file(wav_file: &str, mode: PlayMode) -> Ev3Result<SoundHandle> {
+    let wav_file = wav_file.to_owned();
+    let looping = mode == PlayMode::Loop;
+    let mut handle = SoundHandle::spawn("play_file", move |stop| {
+        play_file_until_stopped(&wav_file, looping, stop)
+    })?;
+
+    if mode == PlayMode::WaitForComplete {
+        handle.wait()?;
+    }
+    Ok(handle)
+}
+
+fn play_file_until_stopped(wav_file: &str, looping: bool, stop: &AtomicBool) -> Ev3Result<()> {
+    let native = Wav::load(wav_file).and_then(|wav| Ok((Pcm::for_wav(&wav)?, wav)));
+    match native {
+        Ok((mut pcm, wav)) => {
+            // Nothing to play, looping would only spin.
+            if wav.data.len() < wav.get_frame_size().max(1) {
+                return Ok(());
+            }
+            // Write chunks of 100ms to react to `stop()`.
+            let chunk = wav.get_frame_size().max(1) * (wav.sample_rate as usize / 10).max(1);
+            loop {
+                if stop.load(Ordering::Relaxed) {
+                    return pcm.stop();
+                }
+                for data in wav.data.chunks(chunk) {
+                    if stop.load(Ordering::Relaxed) {
+                        return pcm.stop();
+                    }
+                    pcm.write(data)?;
+                }
+                if !looping {
+                    return pcm.drain();
+                }
+            }
+        }
+        Err(_) => loop {
+            if !wait_unless_stopped(&mut aplay(wav_file)?, stop)? || !looping {
+                return Ok(());
+            }
+        },
+    }
+}
+
+/// Play a wav file in the background, like `play_file()` with `PlayMode::NoWait`.
+///
+/// Uses the ALSA PCM device and falls back to `aplay`.
+pub fn play(wav_file: &str) -> Ev3Result<SoundHandle> {
+    play_file(wav_file, PlayMode::NoWait)
+}
+
+/// Play a wav file with `aplay`.
+fn aplay(wav_file: &str) -> Ev3Result<SoundHandle> {
+    let aplay = Command::new("/usr/bin/aplay")
+        .arg("-q")
+        .arg("-Dplug:dmix")
+        .arg(wav_file)
+        .stdout(Stdio::null())
+        .spawn()?;
+    Ok(SoundHandle::from_processes(vec![(
+        "aplay".to_owned(),
+        aplay,
+    )]))
+}
+
+/// Speak the given text aloud with the default `SpeakOptions`. Stopping the handle stops `espeak` and `aplay`.
+pub fn speak(text: &str) -> Ev3Result<SoundHandle> {
+    SpeakOptions::default().speak(text)
+}
+
+/// Get the main channel name or 'Playback' if not available.
+/// Asks the ALSA mixer and falls back to `amixer scontrols`.
+fn get_channels() -> Ev3Result<Vec<String>> {
+    if let Ok(channels) = Mixer::new().and_then(|mixer| mixer.get_channels()) {
+        if !channels.is_empty() {
+            return Ok(channels);
+        }
+    }
+
+    let out = String::from_utf8(
+        Command::new("/usr/bin/amixer")
+            .arg("scontrols")
+            .output()?
+            .stdout,
+    )?;
+
+    let mut channels: Vec<String> = out
+        .split('\n')
+        .filter_map(|line| {
+            let vol_start = line.find('\'').unwrap_or(0) + 1;
+            let vol_end = line.rfind('\'').unwrap_or(1);
+
+            if vol_start >= vol_end {
+                None
+            } else {
+                Some(line[vol_start..vol_end].to_owned())
+            }
+        })
+        .collect();
+
+    if channels.is_empty() {
+        channels.push("Playback".to_owned());
+    }
+
+    Ok(channels)
+}
+
+/// Sets the sound volume to the given percentage [0-100] with the ALSA mixer,
+/// or by calling `amixer -q set <channel> <pct>%` if the mixer is not available.
+pub fn set_volume_channel(volume: i32, channel: &str) -> Ev3Result<()> {
+    if let Ok(mixer) = Mixer::new() {
+        if mixer.set_volume(channel, volume).is_ok() {
+            return Ok(());
+        }
+    }
+
+    Command::new("/usr/bin/amixer")
+        .args(["-q", "set", channel, &format!("{volume}%")])
+        .stdout(Stdio::null())
+        .spawn()?
+        .wait()?;
+
+    Ok(())
+}
+
+/// Sets the sound volume to the given percentage [0-100] of all channels,
+/// see `set_volume_channel()`.
+/// It tries to determine the default channel
+/// by running `amixer scontrols`. If that fails as well, it uses the
+/// `Playback` channel, as that is the only channel on the EV3.
+pub fn set_volume(volume: i32) -> Ev3Result<()> {
+    for channel in get_channels()? {
+        set_volume_channel(volume, &channel)?;
+    }
+    Ok(())
+}
+
+/// Gets the current sound volume with the ALSA mixer,
+/// or by parsing the output of `amixer get <channel>` if the mixer is not available.
+pub fn get_volume_channel(channel: &str) -> Ev3Result<i32> {
+    if let Ok(volume) = Mixer::new().and_then(|mixer| mixer.get_volume(channel)) {
+        return Ok(volume);
+    }
+
+    let out = String::from_utf8(
+        Command::new("/usr/bin/amixer")
+            .args(["get", channel])
+            .output()?
+            .stdout,
+    )?;
+
+    let vol_start = out.find('[').unwrap_or(0) + 1;
+    let vol_end = out.find("%]").unwrap_or(1);
+    let vol = &out[vol_start..vol_end].parse::<i32>()?;
+
+    Ok(*vol)
+}
+
+/// Gets the current sound volume of the main channel, see `get_volume_channel()`.
+/// It tries to determine the default channel
+/// by running `amixer scontrols`. If that fails as well, it uses the
+/// `Playback` channel, as that is the only channel on the EV3.
+pub fn get_volume() -> Ev3Result<i32> {
+    get_volume_channel(&get_channels()?[0])
+}
@@ -0,0 +1,382 @@
+//! WAV files and ALSA PCM playback with raw `ioctl`s on the sound device.
+
+use super::{ioc, ioctl, IOC_NONE, IOC_READ, IOC_WRITE};
+use crate::{Ev3Error, Ev3Result};
+use std::fs::{self, File, OpenOptions};
+use std::mem::size_of;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::time::Duration;
+
+/// PCM playback device of the first sound card.
+pub const PCM_PATH: &str = "/dev/snd/pcmC0D0p";
+
+/// Parameter indices and values of `include/uapi/sound/asound.h`.
+const SNDRV_PCM_HW_PARAM_ACCESS: usize = 0;
+const SNDRV_PCM_HW_PARAM_FORMAT: usize = 1;
+const SNDRV_PCM_HW_PARAM_SUBFORMAT: usize = 2;
+const SNDRV_PCM_HW_PARAM_SAMPLE_BITS: usize = 8;
+const SNDRV_PCM_HW_PARAM_CHANNELS: usize = 10;
+const SNDRV_PCM_HW_PARAM_RATE: usize = 11;
+const SNDRV_PCM_HW_PARAM_FIRST_INTERVAL: usize = SNDRV_PCM_HW_PARAM_SAMPLE_BITS;
+
+const SNDRV_PCM_ACCESS_RW_INTERLEAVED: u32 = 3;
+const SNDRV_PCM_FORMAT_U8: u32 = 1;
+const SNDRV_PCM_FORMAT_S16_LE: u32 = 2;
+const SNDRV_PCM_FORMAT_S32_LE: u32 = 10;
+const SNDRV_PCM_FORMAT_S24_3LE: u32 = 32;
+const SNDRV_PCM_SUBFORMAT_STD: u32 = 0;
+
+/// `integer` bit of `struct snd_interval`.
+const INTERVAL_INTEGER: u32 = 1 << 2;
+
+const SNDRV_PCM_IOCTL_HW_PARAMS: u32 = ioc(IOC_READ | IOC_WRITE, b'A', 0x11, size_of::<HwParams>());
+const SNDRV_PCM_IOCTL_PREPARE: u32 = ioc(IOC_NONE, b'A', 0x40, 0);
+const SNDRV_PCM_IOCTL_DROP: u32 = ioc(IOC_NONE, b'A', 0x43, 0);
+const SNDRV_PCM_IOCTL_DRAIN: u32 = ioc(IOC_NONE, b'A', 0x44, 0);
+const SNDRV_PCM_IOCTL_WRITEI_FRAMES: u32 = ioc(IOC_WRITE, b'A', 0x50, size_of::<XferI>());
+
+/// WAVE format tags of the `fmt ` chunk.
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Mask {
+    bits: [u32; 8],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct Interval {
+    min: u32,
+    max: u32,
+    flags: u32,
+}
+
+#[repr(C)]
+struct HwParams {
+    flags: u32,
+    masks: [Mask; 3],
+    mres: [Mask; 5],
+    intervals: [Interval; 12],
+    ires: [Interval; 9],
+    rmask: u32,
+    cmask: u32,
+    info: u32,
+    msbits: u32,
+    rate_num: u32,
+    rate_den: u32,
+    fifo_size: libc::c_ulong,
+    reserved: [u8; 64],
+}
+
+impl HwParams {
+    /// Parameters that allow any configuration, like `snd_pcm_hw_params_any()`.
+    fn any() -> Self {
+        let mut params: HwParams = unsafe { std::mem::zeroed() };
+        for mask in params.masks.iter_mut() {
+            mask.bits = [u32::MAX; 8];
+        }
+        for interval in params.intervals.iter_mut() {
+            interval.max = u32::MAX;
+        }
+        params.rmask = u32::MAX;
+        params.info = u32::MAX;
+        params
+    }
+
+    fn set_mask(&mut self, parameter: usize, value: u32) {
+        let mask = &mut self.masks[parameter];
+        mask.bits = [0; 8];
+        mask.bits[(value / 32) as usize] = 1 << (value % 32);
+    }
+
+    fn set_interval(&mut self, parameter: usize, value: u32) {
+        let interval = &mut self.intervals[parameter - SNDRV_PCM_HW_PARAM_FIRST_INTERVAL];
+        interval.min = value;
+        interval.max = value;
+        interval.flags = INTERVAL_INTEGER;
+    }
+}
+
+#[repr(C)]
+struct XferI {
+    result: libc::c_long,
+    buf: *const libc::c_void,
+    frames: libc::c_ulong,
+}
+
+/// Uncompressed PCM samples of a WAV file.
+///
+/// # Example
+/// ```
+/// use ev3dev_lang_rust::sound::Wav;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let wav = Wav::new(1, 8000, 16, vec![0; 16000]);
+/// assert_eq!(wav.get_duration(), Duration::from_secs(1));
+///
+/// let bytes = wav.to_bytes();
+/// assert!(bytes.starts_with(b"RIFF"));
+/// assert_eq!(Wav::parse(&bytes)?, wav);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wav {
+    /// Number of interleaved channels.
+    pub channels: u16,
+    /// Frames per second.
+    pub sample_rate: u32,
+    /// Bits per sample: 8 (unsigned), 16, 24 or 32 (signed little endian).
+    pub bits_per_sample: u16,
+    /// Interleaved samples.
+    pub data: Vec<u8>,
+}
+
+impl Wav {
+    /// Create a WAV from interleaved samples.
+    pub fn new(channels: u16, sample_rate: u32, bits_per_sample: u16, data: Vec<u8>) -> Self {
+        Wav {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            data,
+        }
+    }
+
+    /// Parses a RIFF WAVE file with PCM samples.
+    pub fn parse(bytes: &[u8]) -> Ev3Result<Wav> {
+        let invalid = |msg: &str| Ev3Error::InternalError {
+            msg: format!("Invalid WAV file: {msg}"),
+        };
+
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(invalid("missing RIFF WAVE header"));
+        }
+
+        let u16_at = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+
+        let mut format = None;
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let start = offset + 8;
+            // Streamed files may have a wrong chunk size, the data chunk is clamped to the file.
+            let end = start
+                .saturating_add(u32_at(offset + 4) as usize)
+                .min(bytes.len());
+
+            match id {
+                b"fmt " => {
+                    if end - start < 16 {
+                        return Err(invalid("fmt chunk too short"));
+                    }
+                    let tag = u16_at(start);
+                    if tag != WAVE_FORMAT_PCM && tag != WAVE_FORMAT_EXTENSIBLE {
+                        return Err(invalid(&format!("unsupported format {tag:#06x}")));
+                    }
+                    format = Some((u16_at(start + 2), u32_at(start + 4), u16_at(start + 14)));
+                }
+                b"data" => {
+                    let (channels, sample_rate, bits_per_sample) =
+                        format.ok_or_else(|| invalid("data chunk before fmt chunk"))?;
+                    return Ok(Wav::new(
+                        channels,
+                        sample_rate,
+                        bits_per_sample,
+                        bytes[start..end].to_vec(),
+                    ));
+                }
+                _ => {}
+            }
+
+            // Chunks are padded to an even size.
+            offset = end + (end - start) % 2;
+        }
+
+        Err(invalid("missing data chunk"))
+    }
+
+    /// Reads and parses a WAV file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Ev3Result<Wav> {
+        Wav::parse(&fs::read(path)?)
+    }
+
+    /// Encodes the samples as RIFF WAVE file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let block_align = self.channels * self.bits_per_sample.div_ceil(8);
+        let byte_rate = self.sample_rate * block_align as u32;
+        let padding = self.data.len() % 2;
+
+        let mut bytes = Vec::with_capacity(44 + self.data.len() + padding);
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&((36 + self.data.len() + padding) as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+        bytes.extend_from_slice(&self.channels.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&byte_rate.to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes.resize(bytes.len() + padding, 0);
+        bytes
+    }
+
+    /// Returns the number of bytes per frame (one sample of each channel).
+    pub fn get_frame_size(&self) -> usize {
+        self.channels as usize * (self.bits_per_sample as usize).div_ceil(8)
+    }
+
+    /// Returns the playing time of the samples.
+    pub fn get_duration(&self) -> Duration {
+        let frames = (self.data.len() / self.get_frame_size().max(1)) as u64;
+        Duration::from_micros(frames * 1_000_000 / self.sample_rate.max(1) as u64)
+    }
+}
+
+/// Playback stream of an ALSA PCM device, configured for one sample format.
+///
+/// The device is used directly, without the `dmix` software mixer.
+/// Opening fails if another program plays sound or if the hardware does not support the format,
+/// e.g. stereo files on the EV3. `sound::play_file()` falls back to `aplay` in these cases.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::{Pcm, Wav};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let wav = Wav::load("bark.wav")?;
+/// let mut pcm = Pcm::for_wav(&wav)?;
+/// pcm.write(&wav.data)?;
+/// pcm.drain()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Pcm {
+    file: File,
+    frame_size: usize,
+}
+
+impl Pcm {
+    /// Opens the PCM device at `path` for interleaved samples with the given format.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        channels: u16,
+        sample_rate: u32,
+        bits_per_sample: u16,
+    ) -> Ev3Result<Pcm> {
+        let format = match bits_per_sample {
+            8 => SNDRV_PCM_FORMAT_U8,
+            16 => SNDRV_PCM_FORMAT_S16_LE,
+            24 => SNDRV_PCM_FORMAT_S24_3LE,
+            32 => SNDRV_PCM_FORMAT_S32_LE,
+            _ => {
+                return Err(Ev3Error::InternalError {
+                    msg: format!("Unsupported sample size of {bits_per_sample} bits"),
+                })
+            }
+        };
+
+        // Open non blocking to fail instead of waiting if the device is in use.
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+        unsafe {
+            let flags = libc::fcntl(file.as_raw_fd(), libc::F_GETFL);
+            libc::fcntl(file.as_raw_fd(), libc::F_SETFL, flags & !libc::O_NONBLOCK);
+        }
+
+        let mut params = HwParams::any();
+        params.set_mask(SNDRV_PCM_HW_PARAM_ACCESS, SNDRV_PCM_ACCESS_RW_INTERLEAVED);
+        params.set_mask(SNDRV_PCM_HW_PARAM_FORMAT, format);
+        params.set_mask(SNDRV_PCM_HW_PARAM_SUBFORMAT, SNDRV_PCM_SUBFORMAT_STD);
+        params.set_interval(SNDRV_PCM_HW_PARAM_SAMPLE_BITS, bits_per_sample as u32);
+        params.set_interval(SNDRV_PCM_HW_PARAM_CHANNELS, channels as u32);
+        params.set_interval(SNDRV_PCM_HW_PARAM_RATE, sample_rate);
+        ioctl(&file, SNDRV_PCM_IOCTL_HW_PARAMS, &mut params).map_err(|err| {
+            Ev3Error::InternalError {
+                msg: format!(
+                    "Sound device does not support {channels} channel(s) at {sample_rate} Hz with {bits_per_sample} bits: {err}"
+                ),
+            }
+        })?;
+        ioctl(&file, SNDRV_PCM_IOCTL_PREPARE, std::ptr::null_mut::<()>())?;
+
+        Ok(Pcm {
+            file,
+            frame_size: channels as usize * (bits_per_sample as usize).div_ceil(8),
+        })
+    }
+
+    /// Opens the default PCM device `/dev/snd/pcmC0D0p` for the format of the WAV.
+    pub fn for_wav(wav: &Wav) -> Ev3Result<Pcm> {
+        Pcm::open(PCM_PATH, wav.channels, wav.sample_rate, wav.bits_per_sample)
+    }
+
+    /// Writes interleaved samples, blocking until they are queued for playback.
+    /// Incomplete frames at the end are ignored. Buffer underruns are recovered.
+    pub fn write(&mut self, data: &[u8]) -> Ev3Result<()> {
+        let frames = data.len() / self.frame_size.max(1);
+        let mut written = 0;
+
+        while written < frames {
+            let mut transfer = XferI {
+                result: 0,
+                buf: data[written * self.frame_size..].as_ptr() as *const libc::c_void,
+                frames: (frames - written) as libc::c_ulong,
+            };
+            match ioctl(&self.file, SNDRV_PCM_IOCTL_WRITEI_FRAMES, &mut transfer) {
+                Ok(()) => written += transfer.result.max(0) as usize,
+                Err(err) if err.raw_os_error() == Some(libc::EPIPE) => {
+                    ioctl(
+                        &self.file,
+                        SNDRV_PCM_IOCTL_PREPARE,
+                        std::ptr::null_mut::<()>(),
+                    )?;
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Blocks until all written samples are played.
+    pub fn drain(&mut self) -> Ev3Result<()> {
+        ioctl(
+            &self.file,
+            SNDRV_PCM_IOCTL_DRAIN,
+            std::ptr::null_mut::<()>(),
+        )?;
+        Ok(())
+    }
+
+    /// Stops playback immediately and discards all pending samples.
+    pub fn stop(&mut self) -> Ev3Result<()> {
+        ioctl(&self.file, SNDRV_PCM_IOCTL_DROP, std::ptr::null_mut::<()>())?;
+        ioctl(
+            &self.file,
+            SNDRV_PCM_IOCTL_PREPARE,
+            std::ptr::null_mut::<()>(),
+        )?;
+        Ok(())
+    }
+}
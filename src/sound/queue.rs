@@ -0,0 +1,232 @@
+//! Sequential playback of sounds in the background.
+
+use super::handle::POLL_INTERVAL;
+use super::{play_file, speak, Melody, PlayMode, SoundHandle};
+use crate::Ev3Result;
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+/// A sound clip for the `SoundQueue`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sound {
+    /// Wav file, see `sound::play_file()`.
+    File(String),
+    /// Tone with the frequency in Hz and the duration in milliseconds.
+    Tone(f32, i32),
+    /// Melody, see `Melody::play()`.
+    Melody(Melody),
+    /// Text to speak, see `sound::speak()`.
+    Speech(String),
+}
+
+impl Sound {
+    /// Starts playing the sound in the background.
+    pub fn play(&self) -> Ev3Result<SoundHandle> {
+        match self {
+            Sound::File(wav_file) => play_file(wav_file, PlayMode::NoWait),
+            Sound::Tone(frequency, duration) => {
+                let mut melody = Melody::new(120);
+                melody.add_tone(*frequency, *duration, 0);
+                melody.play()
+            }
+            Sound::Melody(melody) => melody.play(),
+            Sound::Speech(text) => speak(text),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queue: VecDeque<Sound>,
+    current: Option<SoundHandle>,
+    /// A sound was taken from the queue and is about to start or playing.
+    busy: bool,
+    /// `skip()` was called while the current sound was starting, the worker stops it once it is started.
+    skip_pending: bool,
+    closed: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Plays sounds one after another in a background thread, without overlapping.
+///
+/// Sounds that cannot be played are skipped. Dropping the queue stops the current sound.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::{Sound, SoundQueue};
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let queue = SoundQueue::new()?;
+/// queue.push(Sound::Speech("Starting".to_owned()));
+/// queue.push(Sound::Tone(440.0, 200));
+/// queue.push(Sound::File("bark.wav".to_owned()));
+///
+/// // Do something else while the sounds are played...
+///
+/// queue.wait();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SoundQueue {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl SoundQueue {
+    /// Create an empty queue and start its background thread.
+    pub fn new() -> Ev3Result<Self> {
+        let shared = Arc::new(Shared::default());
+        let worker_shared = shared.clone();
+        let worker = thread::Builder::new()
+            .name("sound-queue".to_owned())
+            .spawn(move || run(&worker_shared))?;
+
+        Ok(SoundQueue {
+            shared,
+            worker: Some(worker),
+        })
+    }
+
+    /// Appends a sound, it is played after all sounds before it are finished.
+    pub fn push(&self, sound: Sound) {
+        self.shared.lock().queue.push_back(sound);
+        self.shared.changed.notify_all();
+    }
+
+    /// Returns the number of sounds waiting to be played, without the current sound.
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+
+    /// Checks if no sounds are waiting to be played.
+    pub fn is_empty(&self) -> bool {
+        self.shared.lock().queue.is_empty()
+    }
+
+    /// Checks if a sound is playing or waiting to be played.
+    pub fn is_playing(&self) -> bool {
+        let state = self.shared.lock();
+        state.busy || !state.queue.is_empty()
+    }
+
+    /// Stops the current sound and continues with the next one.
+    pub fn skip(&self) -> Ev3Result<()> {
+        let current = take_current(&mut self.shared.lock());
+        stop_sound(current)
+    }
+
+    /// Removes all waiting sounds and stops the current sound.
+    pub fn stop(&self) -> Ev3Result<()> {
+        let current = {
+            let mut state = self.shared.lock();
+            state.queue.clear();
+            take_current(&mut state)
+        };
+        stop_sound(current)
+    }
+
+    /// Blocks until all sounds are played.
+    pub fn wait(&self) {
+        let mut state = self.shared.lock();
+        while state.busy || !state.queue.is_empty() {
+            state = self
+                .shared
+                .changed
+                .wait(state)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+    }
+}
+
+impl Drop for SoundQueue {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        let _ = self.stop();
+        self.shared.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Takes the current sound to stop it without holding the lock.
+/// If the current sound is still starting, the worker is asked to stop it.
+fn take_current(state: &mut State) -> Option<SoundHandle> {
+    let current = state.current.take();
+    if current.is_none() && state.busy {
+        state.skip_pending = true;
+    }
+    current
+}
+
+fn stop_sound(current: Option<SoundHandle>) -> Ev3Result<()> {
+    match current {
+        Some(mut current) => current.stop(),
+        None => Ok(()),
+    }
+}
+
+/// Worker loop: takes the next sound, starts it and polls until it is finished.
+fn run(shared: &Shared) {
+    loop {
+        let sound = {
+            let mut state = shared.lock();
+            while state.queue.is_empty() && !state.closed {
+                state = shared
+                    .changed
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner());
+            }
+            if state.closed {
+                return;
+            }
+            state.busy = true;
+            state.skip_pending = false;
+            state.queue.pop_front()
+        };
+
+        // Start without holding the lock, spawning processes may take a moment.
+        let handle = sound.map(|sound| sound.play());
+        if let Some(Ok(handle)) = handle {
+            let mut state = shared.lock();
+            if state.closed || state.skip_pending {
+                state.skip_pending = false;
+                drop(state);
+                let _ = stop_sound(Some(handle));
+            } else {
+                state.current = Some(handle);
+            }
+        }
+
+        loop {
+            let mut state = shared.lock();
+            let playing = state
+                .current
+                .as_mut()
+                .is_some_and(|current| current.is_playing());
+            if !playing {
+                if let Some(mut current) = state.current.take() {
+                    let _ = current.wait();
+                }
+                state.busy = false;
+                shared.changed.notify_all();
+                break;
+            }
+            drop(state);
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+//! Text to speech with `espeak`.
+
+use super::{SoundHandle, Wav};
+use crate::{Ev3Error, Ev3Result};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Parameters of `espeak` for `sound::speak()`.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::SpeakOptions;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let options = SpeakOptions::new().voice("en-us").speed(160).pitch(30);
+/// options.speak("Hello, I am Robot")?.wait()?;
+///
+/// // Render once, play it again later without running espeak.
+/// options.speak_to_file("Obstacle detected", "/tmp/obstacle.wav")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpeakOptions {
+    voice: Option<String>,
+    speed: u32,
+    pitch: u32,
+    amplitude: u32,
+    word_gap: u32,
+}
+
+impl Default for SpeakOptions {
+    fn default() -> Self {
+        SpeakOptions {
+            voice: None,
+            speed: 130,
+            pitch: 50,
+            amplitude: 200,
+            word_gap: 0,
+        }
+    }
+}
+
+impl SpeakOptions {
+    /// Create the default options: default voice, 130 words per minute, pitch 50, amplitude 200 and no word gap.
+    pub fn new() -> Self {
+        SpeakOptions::default()
+    }
+
+    /// Sets the voice or language, e.g. `en`, `de` or `en-us+f3`. See `espeak --voices`.
+    pub fn voice(mut self, voice: &str) -> Self {
+        self.voice = Some(voice.to_owned());
+        self
+    }
+
+    /// Sets the speed in words per minute, `espeak` supports 80 to 450.
+    pub fn speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets the pitch from 0 to 99.
+    pub fn pitch(mut self, pitch: u32) -> Self {
+        self.pitch = pitch;
+        self
+    }
+
+    /// Sets the amplitude (volume) from 0 to 200.
+    pub fn amplitude(mut self, amplitude: u32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Sets the pause between words in units of 10ms.
+    pub fn word_gap(mut self, word_gap: u32) -> Self {
+        self.word_gap = word_gap;
+        self
+    }
+
+    /// Returns the arguments for `espeak`, without the output and the text.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::sound::SpeakOptions;
+    ///
+    /// let options = SpeakOptions::new().voice("de").speed(160).word_gap(2);
+    /// assert_eq!(
+    ///     options.get_espeak_args(),
+    ///     ["-v", "de", "-s", "160", "-p", "50", "-a", "200", "-g", "2"]
+    /// );
+    /// ```
+    pub fn get_espeak_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(voice) = &self.voice {
+            args.push("-v".to_owned());
+            args.push(voice.clone());
+        }
+        for (flag, value) in [
+            ("-s", self.speed),
+            ("-p", self.pitch),
+            ("-a", self.amplitude),
+            ("-g", self.word_gap),
+        ] {
+            args.push(flag.to_owned());
+            args.push(value.to_string());
+        }
+        args
+    }
+
+    fn espeak(&self) -> Command {
+        let mut command = Command::new("/usr/bin/espeak");
+        command.args(self.get_espeak_args());
+        command
+    }
+
+    /// Speak the text aloud with `espeak` piped to `aplay`.
+    /// Stopping the handle stops both processes.
+    pub fn speak(&self, text: &str) -> Ev3Result<SoundHandle> {
+        let mut espeak = self
+            .espeak()
+            .arg("--stdout")
+            .arg(text)
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let aplay = Command::new("/usr/bin/aplay")
+            .arg("-q")
+            .arg("-Dplug:dmix")
+            .stdin(espeak.stdout.take().ok_or(Ev3Error::InternalError {
+                msg: "`espeak` pipe to `aplay` could not be created!".to_owned(),
+            })?)
+            .stdout(Stdio::null())
+            .spawn()?;
+
+        Ok(SoundHandle::from_processes(vec![
+            ("espeak".to_owned(), espeak),
+            ("aplay".to_owned(), aplay),
+        ]))
+    }
+
+    /// Renders the speech to a WAV file, e.g. to cache phrases. Blocks until the file is written.
+    pub fn speak_to_file<P: AsRef<Path>>(&self, text: &str, wav_file: P) -> Ev3Result<()> {
+        let output = self
+            .espeak()
+            .arg("-w")
+            .arg(wav_file.as_ref())
+            .arg(text)
+            .stdout(Stdio::null())
+            .output()?;
+        check_status(&output)
+    }
+
+    /// Renders the speech to the bytes of a WAV file. Blocks until `espeak` is finished.
+    pub fn speak_to_buffer(&self, text: &str) -> Ev3Result<Vec<u8>> {
+        let output = self.espeak().arg("--stdout").arg(text).output()?;
+        check_status(&output)?;
+        Ok(output.stdout)
+    }
+
+    /// Renders the speech to samples that can be played with `Pcm`.
+    pub fn speak_to_wav(&self, text: &str) -> Ev3Result<Wav> {
+        Wav::parse(&self.speak_to_buffer(text)?)
+    }
+}
+
+fn check_status(output: &Output) -> Ev3Result<()> {
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Ev3Error::InternalError {
+            msg: format!(
+                "`espeak` failed with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        })
+    }
+}
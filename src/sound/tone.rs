@@ -0,0 +1,87 @@
+//! Tones of the EV3 speaker via the `EV_SND` input device.
+
+use crate::Ev3Result;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::mem::size_of;
+use std::thread;
+use std::time::Duration;
+
+/// Input device of the EV3 sound driver that generates tones.
+pub const TONE_DEVICE_PATH: &str = "/dev/input/by-path/platform-sound-event";
+
+/// Event type and code of `include/uapi/linux/input-event-codes.h`.
+const EV_SND: u16 = 0x12;
+const SND_TONE: u16 = 0x02;
+
+/// Tone generator of the EV3 sound driver.
+///
+/// A tone is played until it is stopped or another tone is started, without blocking.
+/// The tone is stopped when the device is dropped.
+///
+/// # Example
+/// ```no_run
+/// use ev3dev_lang_rust::sound::ToneDevice;
+/// use std::time::Duration;
+///
+/// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+/// let device = ToneDevice::new()?;
+/// device.tone(440.0, Duration::from_millis(200))?;
+///
+/// device.start(880.0)?;
+/// std::thread::sleep(Duration::from_millis(200));
+/// device.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ToneDevice {
+    file: File,
+}
+
+impl ToneDevice {
+    /// Opens the tone generator `/dev/input/by-path/platform-sound-event`.
+    pub fn new() -> Ev3Result<Self> {
+        let file = OpenOptions::new().write(true).open(TONE_DEVICE_PATH)?;
+        Ok(ToneDevice { file })
+    }
+
+    /// Starts a tone with the given frequency in Hz. A frequency of 0 stops the tone.
+    pub fn start(&self, frequency: f32) -> Ev3Result<()> {
+        let event = libc::input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_: EV_SND,
+            code: SND_TONE,
+            value: frequency.max(0.0).round() as i32,
+        };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const libc::input_event as *const u8,
+                size_of::<libc::input_event>(),
+            )
+        };
+        (&self.file).write_all(bytes)?;
+        Ok(())
+    }
+
+    /// Stops the current tone.
+    pub fn stop(&self) -> Ev3Result<()> {
+        self.start(0.0)
+    }
+
+    /// Plays a tone for the given duration and blocks until it is finished.
+    pub fn tone(&self, frequency: f32, duration: Duration) -> Ev3Result<()> {
+        self.start(frequency)?;
+        thread::sleep(duration);
+        self.stop()
+    }
+}
+
+impl Drop for ToneDevice {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
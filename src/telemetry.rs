@@ -0,0 +1,382 @@
+//! Records motor and sensor attributes at a fixed rate for offline analysis.
+//!
+//! The `Recorder` samples a configurable set of attributes on a background thread
+//! into a ring buffer. The recorded samples can be exported as CSV or JSON Lines,
+//! e.g. to plot the step response while tuning the speed PID of a motor.
+//!
+//! # Example
+//! ```no_run
+//! use ev3dev_lang_rust::motors::{LargeMotor, MotorPort};
+//! use ev3dev_lang_rust::telemetry::Recorder;
+//! use std::thread;
+//! use std::time::Duration;
+//!
+//! # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+//! let motor = LargeMotor::get(MotorPort::OutA)?;
+//!
+//! let mut recorder = Recorder::new(Duration::from_millis(10), 1000);
+//! recorder.add_motor("motor", &motor);
+//! recorder.start()?;
+//!
+//! motor.set_speed_sp(500)?;
+//! motor.run_timed(Some(Duration::from_secs(2)))?;
+//! thread::sleep(Duration::from_secs(3));
+//!
+//! recorder.stop();
+//! recorder.export_csv("step_response.csv")?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{Attribute, Device, Ev3Error, Ev3Result};
+
+/// A single set of recorded values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Time since the recorder was started.
+    pub timestamp: Duration,
+    /// Raw attribute values in the order of the recorder channels.
+    /// Attributes that could not be read are recorded as empty string.
+    pub values: Vec<String>,
+}
+
+/// Samples attributes at a fixed rate on a background thread into a ring buffer.
+#[derive(Debug)]
+pub struct Recorder {
+    interval: Duration,
+    capacity: usize,
+    channels: Vec<(String, Attribute)>,
+    buffer: Arc<Mutex<VecDeque<Sample>>>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Recorder {
+    /// Create a new recorder that takes a sample every `interval`
+    /// and keeps at most the latest `capacity` samples.
+    /// Intervals below 1ms are raised to 1ms.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::telemetry::Recorder;
+    /// use ev3dev_lang_rust::Attribute;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let path = std::env::temp_dir().join("ev3dev-recorder-zero-interval");
+    /// std::fs::write(&path, "0")?;
+    ///
+    /// let mut recorder = Recorder::new(Duration::ZERO, 10);
+    /// recorder.add_attribute("value", Attribute::from_path(&path)?);
+    /// recorder.start()?;
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// recorder.stop();
+    /// assert!(!recorder.is_running());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(interval: Duration, capacity: usize) -> Recorder {
+        Recorder {
+            interval: interval.max(Duration::from_millis(1)),
+            capacity: capacity.max(1),
+            channels: Vec::new(),
+            buffer: Arc::new(Mutex::new(VecDeque::new())),
+            running: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        }
+    }
+
+    /// Add an attribute to record under the column `name`.
+    ///
+    /// Channels added while the recorder is running take effect on the next `start()`.
+    pub fn add_attribute(&mut self, name: &str, attribute: Attribute) {
+        self.channels.push((name.to_owned(), attribute));
+    }
+
+    /// Add the attribute `attribute_name` of the `device` to record under the column `name`.
+    pub fn add_device_attribute(&mut self, name: &str, device: &dyn Device, attribute_name: &str) {
+        self.add_attribute(name, device.get_attribute(attribute_name));
+    }
+
+    /// Add the `position`, `speed`, `duty_cycle` and `state` attributes of a tacho motor.
+    /// The column names are prefixed with `prefix`, e.g. `left.position`.
+    pub fn add_motor(&mut self, prefix: &str, motor: &dyn Device) {
+        for attribute_name in ["position", "speed", "duty_cycle", "state"] {
+            self.add_device_attribute(&format!("{prefix}.{attribute_name}"), motor, attribute_name);
+        }
+    }
+
+    /// Add the first `num_values` `value<N>` attributes of a sensor.
+    /// The column names are prefixed with `prefix`, e.g. `gyro.value0`.
+    pub fn add_sensor(&mut self, prefix: &str, sensor: &dyn Device, num_values: u8) {
+        for index in 0..num_values.min(8) {
+            let attribute_name = format!("value{index}");
+            self.add_device_attribute(
+                &format!("{prefix}.{attribute_name}"),
+                sensor,
+                &attribute_name,
+            );
+        }
+    }
+
+    /// Returns the column names of all channels.
+    pub fn get_channel_names(&self) -> Vec<String> {
+        self.channels.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Start sampling on a background thread. Previously recorded samples are discarded.
+    ///
+    /// Returns an error if the recorder is already running, no channels have been added
+    /// or a channel is named `time`, which is reserved for the timestamp column.
+    pub fn start(&mut self) -> Ev3Result<()> {
+        if self.is_running() {
+            return Err(Ev3Error::InternalError {
+                msg: "Recorder is already running".to_owned(),
+            });
+        }
+        if self.channels.is_empty() {
+            return Err(Ev3Error::InternalError {
+                msg: "Recorder has no channels".to_owned(),
+            });
+        }
+        if self.channels.iter().any(|(name, _)| name == "time") {
+            return Err(Ev3Error::InternalError {
+                msg: "Recorder channel name 'time' is reserved for the timestamp".to_owned(),
+            });
+        }
+
+        self.clear();
+        self.running.store(true, Ordering::SeqCst);
+
+        let interval = self.interval;
+        let capacity = self.capacity;
+        let channels = self.channels.clone();
+        let buffer = self.buffer.clone();
+        let running = self.running.clone();
+
+        let handle = thread::Builder::new()
+            .name("telemetry-recorder".to_owned())
+            .spawn(move || {
+                // Clears `running` even if reading an attribute panics.
+                let _running = RunningGuard(running.clone());
+                let start = Instant::now();
+                let mut next = start;
+
+                while running.load(Ordering::SeqCst) {
+                    let timestamp = start.elapsed();
+                    let values = channels
+                        .iter()
+                        .map(|(_, attribute)| attribute.get::<String>().unwrap_or_default())
+                        .collect();
+
+                    {
+                        let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                        if buffer.len() >= capacity {
+                            buffer.pop_front();
+                        }
+                        buffer.push_back(Sample { timestamp, values });
+                    }
+
+                    // Skip missed ticks instead of sampling in a burst.
+                    let now = Instant::now();
+                    next += interval;
+                    while next <= now {
+                        next += interval;
+                    }
+                    thread::sleep(next - now);
+                }
+            });
+
+        match handle {
+            Ok(handle) => {
+                self.handle = Some(handle);
+                Ok(())
+            }
+            Err(e) => {
+                self.running.store(false, Ordering::SeqCst);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Stop sampling and wait for the background thread to finish.
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Checks if the recorder is currently sampling.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Returns a copy of the recorded samples, oldest first.
+    pub fn get_samples(&self) -> Vec<Sample> {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Discards all recorded samples.
+    pub fn clear(&self) {
+        self.buffer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Writes the recorded samples as CSV with a header line.
+    /// The first column `time` contains the timestamp in seconds.
+    ///
+    /// # Example
+    /// ```
+    /// use ev3dev_lang_rust::telemetry::Recorder;
+    /// use ev3dev_lang_rust::Attribute;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> ev3dev_lang_rust::Ev3Result<()> {
+    /// let path = std::env::temp_dir().join("ev3dev-recorder-example");
+    /// std::fs::write(&path, "42")?;
+    ///
+    /// let mut recorder = Recorder::new(Duration::from_millis(10), 1);
+    /// recorder.add_attribute("speed, \"left\"", Attribute::from_path(&path)?);
+    /// recorder.add_attribute("line\nbreak", Attribute::from_path(&path)?);
+    /// recorder.start()?;
+    /// while recorder.get_samples().is_empty() {
+    ///     std::thread::sleep(Duration::from_millis(1));
+    /// }
+    /// recorder.stop();
+    ///
+    /// let mut csv = Vec::new();
+    /// recorder.write_csv(&mut csv)?;
+    /// let csv = String::from_utf8(csv).unwrap();
+    /// assert!(csv.starts_with("time,\"speed, \"\"left\"\"\",\"line\nbreak\"\n"));
+    /// assert!(csv.ends_with(",42,42\n"));
+    ///
+    /// let mut json = Vec::new();
+    /// recorder.write_json_lines(&mut json)?;
+    /// let json = String::from_utf8(json).unwrap();
+    /// assert!(json.ends_with(",\"speed, \\\"left\\\"\":42,\"line\\nbreak\":42}\n"));
+    ///
+    /// // `time` is reserved for the timestamps.
+    /// recorder.add_attribute("time", Attribute::from_path(&path)?);
+    /// assert!(recorder.start().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> Ev3Result<()> {
+        let mut header = vec!["time".to_owned()];
+        header.extend(self.get_channel_names().iter().map(|name| csv_field(name)));
+        writeln!(writer, "{}", header.join(","))?;
+
+        for sample in self.buffer.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            write!(writer, "{:.6}", sample.timestamp.as_secs_f64())?;
+            for value in &sample.values {
+                write!(writer, ",{}", csv_field(value))?;
+            }
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the recorded samples as JSON Lines, one object per sample.
+    /// The key `time` contains the timestamp in seconds. Numeric values are written as numbers.
+    pub fn write_json_lines<W: Write>(&self, mut writer: W) -> Ev3Result<()> {
+        let names: Vec<String> = self
+            .get_channel_names()
+            .iter()
+            .map(|name| json_string(name))
+            .collect();
+
+        for sample in self.buffer.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            write!(writer, "{{\"time\":{:.6}", sample.timestamp.as_secs_f64())?;
+            for (name, value) in names.iter().zip(&sample.values) {
+                write!(writer, ",{name}:{}", json_value(value))?;
+            }
+            writeln!(writer, "}}")?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the recorded samples as CSV file to `path`.
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> Ev3Result<()> {
+        self.write_csv(BufWriter::new(File::create(path)?))
+    }
+
+    /// Writes the recorded samples as JSON Lines file to `path`.
+    pub fn export_json_lines<P: AsRef<Path>>(&self, path: P) -> Ev3Result<()> {
+        self.write_json_lines(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Clears the running flag when the sampling thread ends.
+struct RunningGuard(Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Formats a value as JSON number if possible, as JSON string otherwise.
+fn json_value(value: &str) -> String {
+    if let Ok(number) = value.parse::<i64>() {
+        return number.to_string();
+    }
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => number.to_string(),
+        _ => json_string(value),
+    }
+}
+
+/// Formats a value as escaped JSON string.
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}